@@ -51,11 +51,11 @@ class UsersController < ApplicationController
       params.permit(:account_id)
     end
 
+    # Logged in (keys match the saved ones) and a curator on the contract. 
     def authenticate(all_keys, account_id)
-      authorized_id = "somebodyelse.testnet"
-      @user = User.find_by(account_id: authorized_id)
+      @user = User.find_by(account_id: account_id)
   
-      if @user.all_keys == all_keys && account_id == authorized_id
+      if !@user.nil? && @user.all_keys == all_keys && helpers.has_role?(account_id, "curator")
         true
       else
         false
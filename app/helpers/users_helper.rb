@@ -45,6 +45,26 @@ module UsersHelper
     end
   end
 
+  def yocto_to_near(amount)
+    (BigDecimal(amount) / BigDecimal(10) ** 24).to_s('F')
+  end
+
+  # Whether the contract granted `role` (e.g. "curator") to the account. 
+  def has_role?(account_id, role)
+    define_constants
+
+    data = @query.function(
+      @contract,
+      'has_role',
+      {
+        "account_id": account_id,
+        "role": role
+      }
+    )["result"]["result"]
+
+    !data.nil? && JSON.parse(data.pack('c*')) == true
+  end
+
   def get_owner_donation(user)
     account_id = user.account_id
 
@@ -13,7 +13,7 @@ window.walletConnection = new WalletConnection(near)
 window.accountId = window.walletConnection.getAccountId()
 
 window.contract = await new Contract(window.walletConnection.account(), nearConfig.contractName, {
-  viewMethods: ['get_id_by_category'],
+  viewMethods: ['get_id_by_category', 'get_list_to_donate'],
   changeMethods: ['generate_template', 'minting_interface'],
 })
 
@@ -34,6 +34,7 @@ function generate_template() {
     var media = document.getElementById("card_img").value;
 
     var template_id = document.getElementById("template_id").value;
+    var beneficiary_id = document.getElementById("beneficiary_id").value;
 
     window.contract.generate_template(
       {
@@ -42,7 +43,8 @@ function generate_template() {
           "title": title,
           "description": description,
           "media": media,
-        }
+        },
+        "beneficiary_id": beneficiary_id
       },
       "30000000000000",
       utils.format.parseNearAmount("0.1")
@@ -53,17 +55,17 @@ function generate_template() {
 
 
 function minting_interface(suffix_token_id) {
-    window.contract.get_id_by_category().then(
+    window.contract.get_list_to_donate().then(
       (a_hashMap) => {
         var hash_of_amounts = {};
         var sum = 0.1;  // to prevent overflow.
 
+        // Categories are keyed by their slug, no need to look up ids. 
         for (var key in a_hashMap) {
-          let id = parseInt(a_hashMap[key]);
           let amount = parseFloat(document.getElementById(key).value);
 
           if (!isNaN(amount)) {
-            hash_of_amounts[id] = amount;
+            hash_of_amounts[key] = utils.format.parseNearAmount(amount.toString());
             sum += (amount + 0.1);
           }
         }
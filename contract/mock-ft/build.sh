@@ -0,0 +1,9 @@
+#!/bin/bash
+set -e
+
+export WASM=mock_ft.wasm
+
+RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release
+mkdir -p res
+cp target/wasm32-unknown-unknown/release/$WASM res/
+ls res -lh
@@ -0,0 +1,36 @@
+//! A bare NEP-141 fungible token to try fungible token donations against 
+//! locally. Anyone can mint: never deploy this anywhere that matters. 
+use near_contract_standards::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, PanicOnDefault};
+
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    token: FungibleToken,
+}
+
+#[near_bindgen]
+impl Contract {
+    #[init]
+    pub fn new() -> Self {
+      Self {
+        token: FungibleToken::new(b"t".to_vec()),
+      }
+    }
+
+
+    /// Mint `amount` to `account_id`, registering it if needed. 
+    pub fn mint(&mut self, account_id: AccountId, amount: U128) {
+      if !self.token.accounts.contains_key(&account_id) {
+        self.token.internal_register_account(&account_id);
+      }
+
+      self.token.internal_deposit(&account_id, amount.0);
+    }
+}
+
+near_contract_standards::impl_fungible_token_core!(Contract, token);
+near_contract_standards::impl_fungible_token_storage!(Contract, token);
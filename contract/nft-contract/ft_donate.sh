@@ -0,0 +1,35 @@
+#!/bin/bash
+# Donate with a NEP-141 token. Without FT set, deploys the mock token of 
+# ../mock-ft on a local network and donates with it: 
+#   NEAR_ENV=local MASTER=test.near bash ft_donate.sh
+# With FT set, uses that token contract, which must be accepted first: 
+#   near call $CONTRACT add_accepted_ft '{"ft_contract_id": "'$FT'"}' --accountId $CONTRACT
+
+export NEAR_ENV=${NEAR_ENV:-local}
+export MASTER=${MASTER:-test.near}
+export CONTRACT=${CONTRACT:-ea_nft.$MASTER}
+export DONOR=${DONOR:-$MASTER}
+export BENEFICIARY=${BENEFICIARY:-$MASTER}  # of category 0. 
+
+if [ -z "$FT" ]; then
+  export FT=mock-ft.$MASTER
+
+  (cd ../mock-ft && bash build.sh)
+  near create-account $FT --masterAccount $MASTER --initialBalance 5
+  near deploy --accountId $FT --wasmFile ../mock-ft/res/mock_ft.wasm --initFunction new --initArgs '{}'
+
+  near call $FT mint '{"account_id": "'$DONOR'", "amount": "1000000000"}' --accountId $DONOR
+  near call $CONTRACT add_accepted_ft '{"ft_contract_id": "'$FT'"}' --accountId $CONTRACT
+fi
+
+# Contract needs storage on the FT contract to receive it, beneficiaries too. 
+near call $FT storage_deposit '{"account_id": "'$CONTRACT'"}' --accountId $DONOR --deposit 0.00125
+near call $FT storage_deposit '{"account_id": "'$BENEFICIARY'"}' --accountId $DONOR --deposit 0.00125
+
+# Donor pays for the storage of the donation tokens minted for them. 
+near call $CONTRACT deposit_storage '{}' --accountId $DONOR --deposit 0.1
+
+near call $FT ft_transfer_call '{"receiver_id": "'$CONTRACT'", "amount": "1000000", "msg": "{\"suffix_token_id\": \"-'$DONOR'\", \"hash_of_amounts\": {\"0\": \"1000000\"}}"}' --accountId $DONOR --depositYocto 1 --gas 300000000000000
+
+near view $FT ft_balance_of '{"account_id": "'$BENEFICIARY'"}'
+near view $CONTRACT get_storage_balance '{"account_id": "'$DONOR'"}'
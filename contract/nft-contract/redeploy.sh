@@ -1,4 +1,6 @@
 #!/bin/bash
+# Starts over from an empty contract: every token is lost. 
+# Use upgrade.sh to deploy new code to a running contract. 
 
 bash build.sh
 export CONTRACT=ea_nft.wabinab.testnet
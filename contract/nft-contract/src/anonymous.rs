@@ -0,0 +1,204 @@
+use crate::*;
+use near_sdk::require;
+
+
+impl Contract {
+    /// Ordered map of the tokens `owner_id` holds per category. Anonymous 
+    /// tokens are held by the contract itself, so for those we keep one 
+    /// map per donor instead, with no view on it. 
+    pub(crate) fn internal_ordered_tokens(
+      &self,
+      donor_id: &AccountId,
+      owner_id: &AccountId,
+    ) -> HashMap<CategoryId, TokenId> {
+      if owner_id == &env::current_account_id() {
+        self.anonymous_tokens_per_donor.get(donor_id).unwrap_or_default()
+      } else {
+        self.tokens_per_owner_ordered.get(owner_id).unwrap_or_default()
+      }
+    }
+
+    /// Write back `internal_ordered_tokens`, removing it when empty. 
+    pub(crate) fn internal_set_ordered_tokens(
+      &mut self,
+      donor_id: &AccountId,
+      owner_id: &AccountId,
+      token_id_list: &HashMap<CategoryId, TokenId>,
+    ) {
+      let (map, account_id) = if owner_id == &env::current_account_id() {
+        (&mut self.anonymous_tokens_per_donor, donor_id)
+      } else {
+        (&mut self.tokens_per_owner_ordered, owner_id)
+      };
+
+      if token_id_list.is_empty() {
+        map.remove(account_id);
+      } else {
+        map.insert(account_id, token_id_list);
+      }
+    }
+
+    /// Reveal the anonymous records of `donor_id` on `token_id`, leaving 
+    /// the ledger itself as it was appended. Returns how many were revealed. 
+    pub(crate) fn internal_reveal_donations(
+      &mut self,
+      donor_id: &AccountId,
+      token_id: &TokenId,
+    ) -> u64 {
+      let mut revealed = 0;
+
+      if let Some(indexes) = self.ledger_by_token_id.get(token_id) {
+        for index in indexes.iter() {
+          let record = self.donation_ledger.get(index).unwrap();
+
+          if !record.anonymous || &record.donor_id != donor_id {
+            continue;
+          }
+
+          if self.revealed_donations.insert(&index) {
+            self.internal_index_ledger_by_account(donor_id, index);
+            revealed += 1;
+          }
+        }
+      }
+
+      revealed
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Reveal your anonymous donations on a token and take it over. 
+    /// Its donations show up under your account from now on. 
+    /// 
+    /// Note "anonymous" means hidden from views and events: 
+    /// transactions themselves are public on chain. 
+    #[payable]
+    pub fn claim_anonymous_token(&mut self, token_id: TokenId) {
+      self.internal_assert_not_paused(PauseScope::Transfers);
+      assert_one_yocto();
+
+      let donor_id = env::predecessor_account_id();
+      let anonymous_donor_id = expect_lightweight(
+        self.anonymous_donor_by_token_id.get(&token_id),
+        "This token isn't held anonymously."
+      );
+      require!(anonymous_donor_id == donor_id, "Only the donor can claim this token.");
+
+      let mut token = expect_lightweight(
+        self.tokens_by_id.get(&token_id),
+        "This token_id cannot be found."
+      );
+      let category_id = expect_lightweight(
+        self.category_by_token_id.get(&token_id),
+        "Cannot find the category of this token. Please contact support."
+      );
+
+      self.anonymous_donor_by_token_id.remove(&token_id);
+
+      let mut anonymous_list = self.internal_ordered_tokens(&donor_id, &env::current_account_id());
+      if anonymous_list.get(&category_id) == Some(&token_id) {
+        anonymous_list.remove(&category_id);
+        self.internal_set_ordered_tokens(&donor_id, &env::current_account_id(), &anonymous_list);
+      }
+
+      // Hand the token over, keeping the contract's approval. 
+      self.internal_remove_token_from_owner(&token.owner_id, &token_id);
+      self.internal_add_token_to_owner(&donor_id, &token_id);
+      token.owner_id = donor_id.clone();
+      self.tokens_by_id.insert(&token_id, &token);
+
+      let mut token_id_list = self.internal_ordered_tokens(&donor_id, &donor_id);
+      if !token_id_list.contains_key(&category_id) {
+        token_id_list.insert(category_id, token_id.clone());
+        self.internal_set_ordered_tokens(&donor_id, &donor_id, &token_id_list);
+      }
+
+      self.internal_reveal_donations(&donor_id, &token_id);
+
+      let nft_transfer_log: EventLog = EventLog {
+        standard: NFT_STANDARD_NAME.to_string(),
+        version: NFT_METADATA_SPEC.to_string(),
+        event: EventLogVariant::NftTransfer(vec![NftTransferLog {
+          authorized_id: None,
+          old_owner_id: env::current_account_id().to_string(),
+          new_owner_id: donor_id.to_string(),
+          token_ids: vec![token_id.to_string()],
+          memo: Some("anonymous donation claimed".to_string()),
+        }]),
+      };
+
+      env::log_str(&nft_transfer_log.to_string());
+    }
+
+
+    /// Reveal your anonymous donations on a token you don't hold, such as 
+    /// an anonymous gift: they show up under your account (ledger, 
+    /// statements) from now on. The token stays with its owner. 
+    /// Returns how many donations were revealed. 
+    #[payable]
+    pub fn reveal_anonymous_donations(&mut self, token_id: TokenId) -> u64 {
+      assert_one_yocto();
+
+      let donor_id = env::predecessor_account_id();
+      require!(
+        self.anonymous_donor_by_token_id.get(&token_id).is_none(),
+        "This token is held for you, use claim_anonymous_token."
+      );
+
+      let revealed = self.internal_reveal_donations(&donor_id, &token_id);
+      require!(revealed > 0, "No anonymous donations of yours on this token.");
+
+      revealed
+    }
+
+
+    /// Whether a token is held by the contract for an anonymous donor. 
+    pub fn is_anonymous_token(&self, token_id: TokenId) -> bool {
+      self.anonymous_donor_by_token_id.get(&token_id).is_some()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn gift_anonymously(contract: &mut Contract) {
+      set_callback(&donor_id(), STORAGE_PER_MINT, vec![PromiseResult::Successful(vec![])]);
+      contract.nft_mint(
+        "animal-gift".to_string(),
+        0,
+        U128(ONE_NEAR),
+        sample_metadata("animal-gift"),
+        friend_id(),
+        None,
+        Some(true),
+        None,
+      );
+    }
+
+    #[test]
+    fn anonymous_gift_can_be_revealed() {
+      let mut contract = setup_contract();
+      gift_anonymously(&mut contract);
+      assert_eq!(credited_donations(&contract, &donor_id()), 0);
+
+      set_caller(&donor_id(), 1);
+      assert_eq!(contract.reveal_anonymous_donations("animal-gift".to_string()), 1);
+      assert_eq!(credited_donations(&contract, &donor_id()), 1);
+      assert_eq!(contract.nft_token("animal-gift".to_string()).unwrap().owner_id, friend_id());
+    }
+
+    #[test]
+    #[should_panic(expected = "No anonymous donations of yours on this token.")]
+    fn others_cannot_reveal_a_gift() {
+      let mut contract = setup_contract();
+      gift_anonymously(&mut contract);
+
+      set_caller(&friend_id(), 1);
+      contract.reveal_anonymous_donations("animal-gift".to_string());
+    }
+}
@@ -41,6 +41,7 @@ impl NonFungibleTokenCore for Contract {
     //allow a specific account ID to approve a token on your behalf
     #[payable]
     fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>) {
+        self.internal_assert_not_paused(PauseScope::Approvals);
         // The user needs to attah enough to pay for storage on the contract.
         assert_at_least_one_yocto();
 
@@ -53,6 +54,12 @@ impl NonFungibleTokenCore for Contract {
           "Predecessor must be the token owner."
         );
 
+        // Soulbound tokens only keep the contract's own approval. 
+        require!(
+          self.internal_is_transferable(&token_id) || account_id == env::current_account_id(),
+          "This token is soulbound: it can only be approved for the contract itself."
+        );
+
         // get next approval ID
         let approval_id: u64 = token.next_approval_id;
 
@@ -161,4 +168,43 @@ impl NonFungibleTokenCore for Contract {
           self.tokens_by_id.insert(&token_id, &token);
         }
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn revoking_works_while_approvals_are_paused() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+      set_caller(&donor_id(), ONE_NEAR / 100);
+      contract.nft_approve("animal-donor".to_string(), friend_id(), None);
+
+      set_caller(&owner_id(), 0);
+      contract.pause(PauseScope::Approvals);
+
+      set_caller(&donor_id(), 1);
+      contract.nft_revoke("animal-donor".to_string(), friend_id());
+      assert!(!contract.nft_is_approved("animal-donor".to_string(), friend_id(), None));
+
+      set_caller(&donor_id(), 1);
+      contract.nft_revoke_all("animal-donor".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "This is paused for now.")]
+    fn approving_is_paused() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+      set_caller(&owner_id(), 0);
+      contract.pause(PauseScope::Approvals);
+
+      set_caller(&donor_id(), ONE_NEAR / 100);
+      contract.nft_approve("animal-donor".to_string(), friend_id(), None);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::*;
+use near_sdk::require;
+
+
+#[near_bindgen]
+impl Contract {
+    /// Burn one of your tokens. The storage it used is refunded to whoever 
+    /// paid for it when it was minted (the donor of a gift, nobody if the 
+    /// contract paid), approvals to you. Its donations stay in the ledger 
+    /// and in every total, so statements and campaigns are unaffected. 
+    /// Its ID won't be minted again. 
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId, memo: Option<String>) {
+      self.internal_assert_not_paused(PauseScope::Transfers);
+      assert_one_yocto();
+
+      let owner_id = env::predecessor_account_id();
+      let token = expect_lightweight(
+        self.tokens_by_id.get(&token_id),
+        "This token_id cannot be found."
+      );
+
+      require!(token.owner_id == owner_id, "Only the token owner can burn it.");
+
+      let storage_payer_id = self.storage_payer_by_token_id.get(&token_id);
+      let initial_storage_usage = env::storage_usage();
+
+      // still counted in `get_owner_donation`. 
+      let category_id = self.category_by_token_id.get(&token_id);
+      self.internal_burn_token(&token_id);
+
+      if let Some(category_id) = category_id {
+        let mut burned = self.burned_donations.get(&owner_id).unwrap_or_default();
+        *burned.entry(category_id).or_insert(0) += token.donate_amount;
+        self.burned_donations.insert(&owner_id, &burned);
+      }
+
+      // approvals you added are refunded to you below; the contract's own 
+      // came with the mint. Remembering the burned ID and donate amount 
+      // takes a bit of the released storage. 
+      let mut approved_account_ids = token.approved_account_ids;
+      approved_account_ids.remove(&env::current_account_id());
+
+      let approvals_storage: u64 = approved_account_ids
+          .keys()
+          .map(bytes_for_approved_account_id)
+          .sum();
+      let released_storage = initial_storage_usage
+          .saturating_sub(env::storage_usage())
+          .saturating_sub(approvals_storage);
+
+      if let Some(storage_payer_id) = storage_payer_id {
+        if released_storage > 0 {
+          Promise::new(storage_payer_id).transfer(
+            Balance::from(released_storage) * env::storage_byte_cost()
+          );
+        }
+      }
+
+      refund_approved_account_ids(owner_id.clone(), &approved_account_ids);
+
+      let nft_burn_log: EventLog = EventLog {
+        standard: NFT_STANDARD_NAME.to_string(),
+        version : NFT_METADATA_SPEC.to_string(),
+        event   : EventLogVariant::NftBurn(vec![NftBurnLog {
+          owner_id     : owner_id.to_string(),
+          token_ids    : vec![token_id],
+          authorized_id: None,
+          memo,
+        }]),
+      };
+
+      env::log_str(&nft_burn_log.to_string());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn burned_id_is_not_minted_again() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+      set_caller(&donor_id(), 1);
+      contract.nft_burn("animal-donor".to_string(), None);
+      assert!(contract.nft_token("animal-donor".to_string()).is_none());
+      assert_eq!(ordered_token(&contract, &donor_id()), None);
+      assert_eq!(
+        contract.get_owner_donation(donor_id()).get("Animal Welfare"),
+        Some(&U128(ONE_NEAR))
+      );
+
+      // its history stays its own. 
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+      assert!(contract.nft_token("animal-donor".to_string()).is_none());
+      assert_eq!(ordered_token(&contract, &donor_id()), Some("animal-donor-2".to_string()));
+
+      // burned and held tokens add up. 
+      assert_eq!(
+        contract.get_owner_donation(donor_id()).get("Animal Welfare"),
+        Some(&U128(2 * ONE_NEAR))
+      );
+
+      for token_id in ["animal-donor", "animal-donor-2"] {
+        assert_eq!(contract.get_donations_by_token(token_id.to_string(), None, Some(10)).len(), 1);
+      }
+    }
+}
@@ -0,0 +1,501 @@
+use crate::*;
+use near_sdk::require;
+
+pub type CampaignId = u64;
+
+
+/// What happens to donations to the category once a campaign ended. 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum AfterDeadline {
+    /// Donations to the category are rejected, until a curator 
+    /// closes the campaign. 
+    Reject,
+    /// Donations go to the category as usual, without counting 
+    /// towards the campaign. 
+    RollOver,
+}
+
+
+/// Whether donations reach the beneficiary right away, or only once
+/// the campaign reached its target. 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignMode {
+    /// Donations are forwarded as usual. 
+    KeepItAll,
+    /// Donations are held in escrow by the contract until the deadline. 
+    /// Released if target is reached, otherwise donors can claim refunds. 
+    AllOrNothing,
+}
+
+
+/// A fundraising campaign for a category: raise `target` between 
+/// `starts_at` and `ends_at` (block timestamp, nanoseconds). 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Campaign {
+    pub category_id: CategoryId,
+    pub title: String,
+    pub description: Option<String>,
+    pub media: Option<String>,  // URL to associated media. 
+    pub target: Balance,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub after_deadline: AfterDeadline,
+    pub mode: CampaignMode,
+
+    // running totals. 
+    pub raised: Balance,
+    pub donors: u64,
+
+    // all-or-nothing only: escrow paid out to the beneficiary. 
+    pub escrow_released: bool,
+
+    // all-or-nothing only: missed its target, refunds are being claimed. 
+    // Set by the first claim, so it can't be released after all. 
+    pub refunding: bool,
+
+    // closed after the deadline: no longer rejects donations. 
+    pub closed: bool,
+}
+
+/// The Json campaign is what will be returned from view calls. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonCampaign {
+    pub campaign_id: CampaignId,
+    pub category_id: CategoryId,
+    pub title: String,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub target: U128,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub after_deadline: AfterDeadline,
+    pub mode: CampaignMode,
+    pub raised: U128,
+    pub donors: u64,
+    pub escrow_released: bool,
+    pub refunding: bool,
+    pub closed: bool,
+}
+
+
+impl Campaign {
+    fn to_json(&self, campaign_id: CampaignId) -> JsonCampaign {
+      JsonCampaign {
+        campaign_id,
+        category_id: self.category_id,
+        title: self.title.clone(),
+        description: self.description.clone(),
+        media: self.media.clone(),
+        target: U128(self.target),
+        starts_at: self.starts_at,
+        ends_at: self.ends_at,
+        after_deadline: self.after_deadline,
+        mode: self.mode,
+        raised: U128(self.raised),
+        donors: self.donors,
+        escrow_released: self.escrow_released,
+        refunding: self.refunding,
+        closed: self.closed,
+      }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+      let now = env::block_timestamp();
+      self.starts_at <= now && now < self.ends_at
+    }
+
+    pub(crate) fn has_ended(&self) -> bool {
+      env::block_timestamp() >= self.ends_at
+    }
+
+    pub(crate) fn is_escrow(&self) -> bool {
+      self.mode == CampaignMode::AllOrNothing
+    }
+}
+
+
+impl Contract {
+    /// Whether the category takes donations right now. 
+    pub(crate) fn internal_accepts_donations(&self, category_id: CategoryId) -> bool {
+      if self.internal_is_archived(category_id) {
+        return false;
+      }
+
+      let campaign_ids = self.campaigns_by_cat_id.get(&category_id).unwrap_or_default();
+
+      campaign_ids.into_iter().all(|campaign_id| {
+        let campaign = self.campaigns.get(campaign_id).unwrap();
+        !(campaign.has_ended() 
+            && !campaign.closed 
+            && campaign.after_deadline == AfterDeadline::Reject)
+      })
+    }
+
+    /// panic if the category doesn't take donations right now. 
+    pub(crate) fn internal_assert_accepts_donations(&self, category_id: CategoryId) {
+      require!(
+        self.internal_accepts_donations(category_id),
+        "This category no longer accepts donations."
+      );
+    }
+
+    /// What `account_id` gave to a campaign, openly and anonymously. 
+    fn internal_campaign_donation(&self, key: &(CampaignId, AccountId)) -> (Balance, Balance) {
+      (
+        self.campaign_donations.get(key).unwrap_or(0),
+        self.anonymous_campaign_donations.get(key).unwrap_or(0),
+      )
+    }
+
+    /// Add a donation to a campaign's totals. Anonymous donations are 
+    /// kept apart, out of `get_campaign_donation`. 
+    fn internal_add_campaign_donation(
+      &mut self,
+      campaign_id: CampaignId,
+      mut campaign: Campaign,
+      account_id: &AccountId,
+      donate_amount: Balance,
+      anonymous: bool,
+    ) {
+      let key = (campaign_id, account_id.clone());
+      let (donated, donated_anonymously) = self.internal_campaign_donation(&key);
+
+      if donated == 0 && donated_anonymously == 0 {
+        campaign.donors += 1;
+      }
+
+      campaign.raised += donate_amount;
+
+      if anonymous {
+        self.anonymous_campaign_donations.insert(&key, &(donated_anonymously + donate_amount));
+      } else {
+        self.campaign_donations.insert(&key, &(donated + donate_amount));
+      }
+
+      self.campaigns.replace(campaign_id, &campaign);
+    }
+
+    /// The all-or-nothing campaign of the category running right now, if any. 
+    pub(crate) fn internal_escrow_campaign(&self, category_id: CategoryId) -> Option<CampaignId> {
+      self.campaigns_by_cat_id
+          .get(&category_id)
+          .unwrap_or_default()
+          .into_iter()
+          .find(|campaign_id| {
+            let campaign = self.campaigns.get(*campaign_id).unwrap();
+            campaign.is_escrow() && campaign.is_active()
+          })
+    }
+
+    /// Where a donation to the category is sent: the contract itself while 
+    /// an all-or-nothing campaign holds it in escrow, otherwise the 
+    /// beneficiary. Returns the escrow campaign too, to be passed on to 
+    /// `internal_record_donation` once the transfer went through. 
+    pub(crate) fn internal_route_donation(
+      &self,
+      category_id: CategoryId,
+    ) -> (AccountId, Option<CampaignId>) {
+      match self.internal_escrow_campaign(category_id) {
+        Some(campaign_id) => (env::current_account_id(), Some(campaign_id)),
+        None => (self.internal_beneficiary(category_id), None),
+      }
+    }
+
+    /// Count a donation towards every running keep-it-all campaign of its 
+    /// category, and towards the campaign holding it in escrow, if any. 
+    /// An escrowed donation is counted even if the campaign ended since 
+    /// it was routed: the money is with us either way. 
+    pub(crate) fn internal_count_campaigns(
+      &mut self,
+      account_id: &AccountId,
+      category_id: CategoryId,
+      donate_amount: Balance,
+      anonymous: bool,
+      escrow_campaign_id: Option<CampaignId>,
+      index: DonationIndex,
+    ) {
+      let campaign_ids = self.campaigns_by_cat_id.get(&category_id).unwrap_or_default();
+
+      for campaign_id in campaign_ids {
+        let campaign = self.campaigns.get(campaign_id).unwrap();
+
+        if !campaign.is_active() || campaign.is_escrow() {
+          continue;
+        }
+
+        self.internal_add_campaign_donation(
+          campaign_id, 
+          campaign, 
+          account_id, 
+          donate_amount, 
+          anonymous
+        );
+      }
+
+      let campaign_id = if let Some(campaign_id) = escrow_campaign_id {
+        campaign_id
+      } else {
+        return;
+      };
+
+      let campaign = self.campaigns.get(campaign_id).unwrap();
+
+      // Released while this donation was on its way: pass it on. 
+      if campaign.escrow_released {
+        Promise::new(self.internal_beneficiary(category_id)).transfer(donate_amount);
+      }
+
+      self.internal_add_campaign_donation(
+        campaign_id, 
+        campaign, 
+        account_id, 
+        donate_amount, 
+        anonymous
+      );
+
+      let key = (campaign_id, account_id.clone());
+      let mut indexes = self.escrowed_donations.get(&key).unwrap_or_default();
+      indexes.push(index);
+      self.escrowed_donations.insert(&key, &indexes);
+    }
+
+    /// Take a refunded donation off its token, which is burned when 
+    /// nothing is left on it. 
+    fn internal_take_off_token(&mut self, token_id: &TokenId, amount: Balance) {
+      let token = if let Some(token) = self.tokens_by_id.get(token_id) {
+        token
+      } else {
+        return;  // burned already. 
+      };
+
+      let donate_amount = token.donate_amount.saturating_sub(amount);
+
+      let has_ft_donations = self.ft_donations_by_token_id
+          .get(token_id)
+          .unwrap_or_default()
+          .values()
+          .any(|amount| *amount > 0);
+
+      if donate_amount == 0 && !has_ft_donations {
+        self.internal_burn_token(token_id);
+        refund_approved_account_ids(token.owner_id, &token.approved_account_ids);
+      } else {
+        self.internal_set_donate_amount(token_id, token, donate_amount);
+      }
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Start a campaign raising `target` for a category. Curators only. 
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_campaign(
+      &mut self,
+      category_id: CategoryKey,
+      title: String,
+      description: Option<String>,
+      media: Option<String>,
+      target: U128,
+      starts_at: u64,
+      ends_at: u64,
+      after_deadline: AfterDeadline,
+      mode: CampaignMode,
+    ) -> CampaignId {
+      let initial_storage_usage = env::storage_usage();
+
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+      require!(target.0 > 0, "Target must be more than 0.");
+      require!(starts_at < ends_at, "Campaign must start before it ends.");
+      require!(
+        ends_at > env::block_timestamp(),
+        "Campaign must end in the future."
+      );
+
+      self.internal_beneficiary(category_id);
+
+      let campaign_id = self.campaigns.len();
+
+      self.campaigns.push(&Campaign {
+        category_id,
+        title,
+        description,
+        media,
+        target: target.0,
+        starts_at,
+        ends_at,
+        after_deadline,
+        mode,
+        raised: 0,
+        donors: 0,
+        escrow_released: false,
+        refunding: false,
+        closed: false,
+      });
+
+      let mut campaign_ids = self.campaigns_by_cat_id.get(&category_id).unwrap_or_default();
+      campaign_ids.push(campaign_id);
+      self.campaigns_by_cat_id.insert(&category_id, &campaign_ids);
+
+      let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+      refund_deposit(required_storage_in_bytes, env::predecessor_account_id());
+
+      campaign_id
+    }
+
+
+    /// Close a campaign after its deadline, so its category takes 
+    /// donations again if it was rejecting them. Escrow can still be 
+    /// released or refunded. Curators only. 
+    pub fn close_campaign(&mut self, campaign_id: CampaignId) {
+      self.internal_assert_role(Role::Curator);
+
+      let mut campaign = expect_lightweight(
+        self.campaigns.get(campaign_id),
+        "Cannot find this campaign."
+      );
+
+      require!(campaign.has_ended(), "Campaign hasn't ended yet.");
+      require!(!campaign.closed, "Campaign already closed.");
+
+      campaign.closed = true;
+      self.campaigns.replace(campaign_id, &campaign);
+    }
+
+
+    pub fn get_campaign(&self, campaign_id: CampaignId) -> Option<JsonCampaign> {
+      self.campaigns.get(campaign_id).map(|campaign| campaign.to_json(campaign_id))
+    }
+
+
+    /// Campaigns currently taking donations. 
+    pub fn get_active_campaigns(
+      &self,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonCampaign> {
+      let start = u128::from(from_index.unwrap_or(U128(0)));
+
+      self.campaigns
+          .iter()
+          .enumerate()
+          .filter(|(_, campaign)| campaign.is_active())
+          .skip(start as usize)
+          .take(limit.unwrap_or(0) as usize)
+          .map(|(campaign_id, campaign)| campaign.to_json(campaign_id as CampaignId))
+          .collect()
+    }
+
+
+    pub fn get_campaigns_for_category(&self, category_id: CategoryKey) -> Vec<JsonCampaign> {
+      let category_id = self.internal_category_id(&category_id);
+
+      self.campaigns_by_cat_id
+          .get(&category_id)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|campaign_id| self.campaigns.get(campaign_id).unwrap().to_json(campaign_id))
+          .collect()
+    }
+
+
+    pub fn get_campaign_donor_count(&self, campaign_id: CampaignId) -> u64 {
+      self.campaigns.get(campaign_id).map(|campaign| campaign.donors).unwrap_or(0)
+    }
+
+
+    /// How much this account gave to the campaign, not anonymously. 
+    pub fn get_campaign_donation(&self, campaign_id: CampaignId, account_id: AccountId) -> U128 {
+      U128(self.campaign_donations.get(&(campaign_id, account_id)).unwrap_or(0))
+    }
+
+
+    /// Pay the escrow of an all-or-nothing campaign that reached its 
+    /// target out to the beneficiary. Anyone can call this after the deadline. 
+    pub fn release_campaign_escrow(&mut self, campaign_id: CampaignId) -> U128 {
+      let mut campaign = expect_lightweight(
+        self.campaigns.get(campaign_id),
+        "Cannot find this campaign."
+      );
+
+      require!(campaign.is_escrow(), "This campaign holds no escrow.");
+      require!(campaign.has_ended(), "Campaign hasn't ended yet.");
+      require!(!campaign.refunding, "Campaign is refunding its donors.");
+      require!(campaign.raised >= campaign.target, "Campaign didn't reach its target.");
+      require!(!campaign.escrow_released, "Escrow already released.");
+
+      campaign.escrow_released = true;
+      self.campaigns.replace(campaign_id, &campaign);
+
+      Promise::new(self.internal_beneficiary(campaign.category_id)).transfer(campaign.raised);
+
+      U128(campaign.raised)
+    }
+
+
+    /// Get your donation back from an all-or-nothing campaign that missed 
+    /// its target; from then on its escrow can't be released. Each escrowed donation is taken off the token it went 
+    /// to (also gifts and anonymous tokens), which is burned when nothing 
+    /// is left on it, and off the quadratic funding tallies. The ledger 
+    /// keeps the donations, marked as refunded. 
+    pub fn claim_campaign_refund(&mut self, campaign_id: CampaignId) -> U128 {
+      let account_id = env::predecessor_account_id();
+      let mut campaign = expect_lightweight(
+        self.campaigns.get(campaign_id),
+        "Cannot find this campaign."
+      );
+
+      require!(campaign.is_escrow(), "This campaign holds no escrow.");
+      require!(campaign.has_ended(), "Campaign hasn't ended yet.");
+      require!(!campaign.escrow_released, "Escrow already released.");
+
+      // Decided once: donations counted late (routed before the deadline) 
+      // must not turn a refunding campaign into a released one. 
+      if !campaign.refunding {
+        require!(campaign.raised < campaign.target, "Campaign reached its target.");
+        campaign.refunding = true;
+      }
+
+      let key = (campaign_id, account_id.clone());
+      let (donated, donated_anonymously) = self.internal_campaign_donation(&key);
+      let refund_amount = donated + donated_anonymously;
+      require!(refund_amount > 0, "Nothing to refund for you in this campaign.");
+
+      self.campaign_donations.remove(&key);
+      self.anonymous_campaign_donations.remove(&key);
+
+      // `raised` is what's left in escrow. 
+      campaign.raised -= refund_amount;
+      campaign.donors -= 1;
+      self.campaigns.replace(campaign_id, &campaign);
+
+      let mut token_id = TokenId::default();
+
+      for index in self.escrowed_donations.remove(&key).unwrap_or_default() {
+        let record = self.donation_ledger.get(index).unwrap();
+
+        self.internal_take_off_token(&record.token_id, record.amount);
+        self.internal_untally_qf(&account_id, record.category_id, record.amount, record.timestamp);
+        self.refunded_donations.insert(&index);
+
+        token_id = record.token_id;
+      }
+
+      self.internal_refund_donation(
+        &account_id,
+        &token_id,
+        refund_amount,
+        donated_anonymously > 0,
+        "Campaign did not reach its target.",
+      );
+
+      U128(refund_amount)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+use crate::*;
+use near_sdk::require;
+
+
+/// A category, given either by its id or by its slug (the `Category` 
+/// string it was generated with). Slugs are never all digits, so as a 
+/// JSON map key, "0" is the id 0 and "animal" a slug. 
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(untagged)]
+pub enum CategoryKey {
+    Id(CategoryId),
+    Slug(Category),
+}
+
+
+fn is_numeric(slug: &str) -> bool {
+  !slug.is_empty() && slug.chars().all(|c| c.is_ascii_digit())
+}
+
+
+impl Contract {
+    /// The id of a category given either way, if it exists. 
+    pub(crate) fn internal_find_category_id(&self, category_key: &CategoryKey) -> Option<CategoryId> {
+      let category_id = match category_key {
+        CategoryKey::Id(category_id) => Some(*category_id),
+        CategoryKey::Slug(slug) if is_numeric(slug) => slug.parse::<CategoryId>().ok(),
+        CategoryKey::Slug(slug) => self.category_id_by_slug.get(slug),
+      };
+
+      category_id.filter(|category_id| self.token_metadata_by_cat_id.get(category_id).is_some())
+    }
+
+    /// Same as `internal_find_category_id`, panics if there's none. 
+    pub(crate) fn internal_category_id(&self, category_key: &CategoryKey) -> CategoryId {
+      expect_lightweight(
+        self.internal_find_category_id(category_key),
+        "Cannot find this category."
+      )
+    }
+
+    pub(crate) fn internal_category_ids(&self, category_keys: Vec<CategoryKey>) -> Vec<CategoryId> {
+      category_keys.iter().map(|key| self.internal_category_id(key)).collect()
+    }
+
+    /// Amounts per category key to amounts per category id. 
+    /// Panics if a category is given twice. 
+    pub(crate) fn internal_category_amounts(
+      &self,
+      hash_of_amounts: HashMap<CategoryKey, U128>,
+    ) -> HashMap<CategoryId, U128> {
+      let mut amounts: HashMap<CategoryId, U128> = HashMap::new();
+
+      for (category_key, amount) in hash_of_amounts {
+        require!(
+          amounts.insert(self.internal_category_id(&category_key), amount).is_none(),
+          "Category given twice, by id and by slug."
+        );
+      }
+
+      amounts
+    }
+
+    /// panic unless `slug` can name a new category. 
+    pub(crate) fn internal_assert_new_slug(&self, slug: &Category) {
+      require!(!slug.is_empty(), "Category cannot be empty.");
+      require!(!is_numeric(slug), "Category cannot be only digits.");
+      require!(
+        self.category_id_by_slug.get(slug).is_none(),
+        "Category already exists."
+      );
+    }
+
+    pub(crate) fn internal_is_archived(&self, category_id: CategoryId) -> bool {
+      self.archived_categories.contains(&category_id)
+    }
+
+    /// Log a change to a category, made by the predecessor. 
+    pub(crate) fn internal_log_category_event(
+      &self, 
+      category_id: CategoryId, 
+      variant: fn(Vec<CategoryLog>) -> EventLogVariant,
+    ) {
+      let category_log: EventLog = EventLog {
+        standard: DONATION_STANDARD_NAME.to_string(),
+        version: DONATION_EVENT_VERSION.to_string(),
+        event: variant(vec![CategoryLog {
+          category_id: category_id.to_string(),
+          category: self.categories.get(category_id as u64).unwrap_or_default(),
+          curator_id: env::predecessor_account_id().to_string(),
+        }]),
+      };
+
+      env::log_str(&category_log.to_string());
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Replace the template metadata of a category. Tokens minted 
+    /// already keep theirs. Curators only. 
+    /// 
+    /// Attach enough for storage if the metadata grows; extra is refunded. 
+    #[payable]
+    pub fn update_category(&mut self, category_id: CategoryKey, metadata: TokenMetadata) {
+      let initial_storage_usage = env::storage_usage();
+
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+
+      self.token_metadata_by_cat_id.insert(&category_id, &metadata);
+      self.internal_log_category_event(category_id, EventLogVariant::CategoryUpdate);
+
+      let required_storage = env::storage_usage().saturating_sub(initial_storage_usage);
+      refund_deposit(required_storage, env::predecessor_account_id());
+    }
+
+
+    /// Stop taking donations to a category. It stays in donor views, 
+    /// but no longer shows in `get_list_to_donate`. Curators only. 
+    pub fn archive_category(&mut self, category_id: CategoryKey) {
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+      require!(!self.internal_is_archived(category_id), "Category is archived already.");
+
+      self.archived_categories.insert(&category_id);
+      self.internal_log_category_event(category_id, EventLogVariant::CategoryArchive);
+    }
+
+
+    /// Take donations to an archived category again. Curators only. 
+    pub fn reactivate_category(&mut self, category_id: CategoryKey) {
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+      require!(self.internal_is_archived(category_id), "Category isn't archived.");
+
+      self.archived_categories.remove(&category_id);
+      self.internal_log_category_event(category_id, EventLogVariant::CategoryReactivate);
+    }
+
+
+    pub fn is_category_archived(&self, category_id: CategoryKey) -> bool {
+      self.internal_find_category_id(&category_id)
+          .map_or(false, |category_id| self.internal_is_archived(category_id))
+    }
+
+
+    /// Id of the category with this slug. 
+    pub fn get_category_id(&self, category: Category) -> Option<CategoryId> {
+      self.category_id_by_slug.get(&category)
+    }
+
+
+    pub fn get_archived_categories(&self) -> Vec<CategoryId> {
+      self.archived_categories.to_vec()
+    }
+}
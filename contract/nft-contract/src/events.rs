@@ -3,7 +3,7 @@ use std::fmt;
 use near_sdk::serde::{Deserialize, Serialize};
 
 /// Enum that represetns the data type of the EventLog. 
-/// Can be either NftMint or NftTransfer
+/// Can be either NftMint, NftTransfer, NftBurn or one of our donation events.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag="event", content="data")]
 #[serde(rename_all="snake_case")]
@@ -12,6 +12,18 @@ use near_sdk::serde::{Deserialize, Serialize};
 pub enum EventLogVariant {
   NftMint(Vec<NftMintLog>),
   NftTransfer(Vec<NftTransferLog>),
+  NftBurn(Vec<NftBurnLog>),
+  DonationRefund(Vec<DonationRefundLog>),
+  CategoryCreate(Vec<CategoryLog>),
+  CategoryUpdate(Vec<CategoryLog>),
+  CategoryArchive(Vec<CategoryLog>),
+  CategoryReactivate(Vec<CategoryLog>),
+  ContractPause(Vec<PauseLog>),
+  ContractUnpause(Vec<PauseLog>),
+  OwnershipPropose(Vec<OwnershipLog>),
+  OwnershipAccept(Vec<OwnershipLog>),
+  OwnershipCancel(Vec<OwnershipLog>),
+  TokenTierChange(Vec<TierLog>),
 }
 
 
@@ -82,3 +94,102 @@ pub struct NftTransferLog {
 }
 
 
+/// An event log to capture token burning
+/// 
+/// Arguments:
+///   owner_id: "owner.near" who held the tokens.
+///   token_ids: ["1", "12345abc"]
+///   authorized_id: (optional) approved account that burned them.
+///   memo: (optional) message.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+  pub owner_id: String,
+  pub token_ids: Vec<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub authorized_id: Option<String>,
+
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub memo: Option<String>,
+}
+
+
+/// An event log to capture a refunded donation
+/// 
+/// Arguments:
+///   account_id: (optional) "donor.near" who got the refund, 
+///     left out for anonymous donations.
+///   token_id: token the donation was meant for.
+///   amount: refunded amount in yoctoNEAR.
+///   reason: why it was refunded.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DonationRefundLog {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub account_id: Option<String>,
+
+  pub token_id: String,
+  pub amount: String,
+  pub reason: String,
+}
+
+
+/// An event log to capture a change to a category
+/// 
+/// Arguments:
+///   category_id: "0", index of the category.
+///   category: "Animal Welfare", its name.
+///   curator_id: "curator.near" who made the change.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CategoryLog {
+  pub category_id: String,
+  pub category: String,
+  pub curator_id: String,
+}
+
+
+/// An event log to capture pausing or unpausing
+/// 
+/// Arguments:
+///   scope: "all", "donations", "transfers" or "approvals".
+///   pauser_id: "pauser.near" who flipped the switch.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseLog {
+  pub scope: String,
+  pub pauser_id: String,
+}
+
+
+/// An event log to capture a step of an ownership transfer
+/// 
+/// Arguments:
+///   owner_id: "owner.near" owner before this step.
+///   pending_owner_id: "dao.near" proposed (or, once accepted, new) owner.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipLog {
+  pub owner_id: String,
+  pub pending_owner_id: String,
+}
+
+
+/// An event log to capture a token moving into another tier
+/// 
+/// Arguments:
+///   token_id: token whose tier changed.
+///   category_id: "0", index of its category.
+///   old_tier: "0", tier before the donation (0 for none).
+///   new_tier: "1", tier after it.
+///   donate_amount: total on the token in yoctoNEAR.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TierLog {
+  pub token_id: String,
+  pub category_id: String,
+  pub old_tier: String,
+  pub new_tier: String,
+  pub donate_amount: String,
+}
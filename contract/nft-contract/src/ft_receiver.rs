@@ -0,0 +1,427 @@
+use crate::*;
+use near_sdk::require;
+
+const GAS_FOR_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_RESOLVE_FT_DONATION: Gas = Gas(20_000_000_000_000);
+const ONE_YOCTO: Balance = 1;
+
+
+/// What `ft_transfer_call` should put in `msg` to donate. 
+/// Same as the arguments of `minting_interface`, amounts in the 
+/// fungible token's smallest unit. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtDonationMsg {
+    pub suffix_token_id: String,
+    pub hash_of_amounts: HashMap<CategoryKey, U128>,
+    pub issued_at: Option<u64>,
+}
+
+
+#[ext_contract(ext_ft)]
+trait FungibleToken {
+    fn ft_transfer(
+      &mut self,
+      receiver_id: AccountId,
+      amount: U128,
+      memo: Option<String>,
+    );
+}
+
+
+#[ext_contract(ext_self)]
+trait FtDonationResolver {
+    fn resolve_ft_donation(
+      &mut self,
+      sender_id: AccountId,
+      ft_contract_id: AccountId,
+      unused_amount: U128,
+      transfers: Vec<BeneficiaryTransfer>,
+    ) -> U128;
+}
+
+
+pub trait FungibleTokenReceiver {
+    /// Donate with a whitelisted NEP-141 token through `ft_transfer_call`. 
+    /// Returns how much of `amount` wasn't used, to be refunded. 
+    fn ft_on_transfer(
+      &mut self,
+      sender_id: AccountId,
+      amount: U128,
+      msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Mint or update a token per category for `sender_id`, just like 
+    /// `batch_donate`, then forward the tokens to the beneficiaries. 
+    /// Storage is paid out of the sender's `deposit_storage` balance. 
+    fn ft_on_transfer(
+      &mut self,
+      sender_id: AccountId,
+      amount: U128,
+      msg: String,
+    ) -> PromiseOrValue<U128> {
+      // Panicking sends the tokens back to the sender. 
+      self.internal_assert_not_paused(PauseScope::Donations);
+      let initial_storage_usage = env::storage_usage();
+      let ft_contract_id = env::predecessor_account_id();
+
+      require!(
+        self.accepted_fts.contains(&ft_contract_id),
+        "This fungible token is not accepted for donations."
+      );
+
+      let donation: FtDonationMsg = expect_lightweight(
+        near_sdk::serde_json::from_str(&msg).ok(),
+        "Cannot parse msg. Please check the donation format."
+      );
+
+      require!(
+        !donation.hash_of_amounts.is_empty(),
+        "Please donate to at least one category."
+      );
+
+      let total_donation: u128 = donation.hash_of_amounts.values().map(|amount| amount.0).sum();
+
+      require!(
+        total_donation <= amount.0,
+        "You transferred less than you want to donate."
+      );
+
+      let mut token_id_list: HashMap<CategoryId, TokenId> = self.tokens_per_owner_ordered
+          .get(&sender_id)
+          .unwrap_or_default();
+
+      let mut minted_token_ids: Vec<TokenId> = Vec::new();
+      let mut donations_per_beneficiary: HashMap<AccountId, Vec<CategoryDonation>> = HashMap::new();
+
+      for (id, amount) in self.internal_category_amounts(donation.hash_of_amounts) {
+        // A transfer of 0 would fail, and refund the whole amount. 
+        require!(amount.0 > 0, "Donation to each category must be more than 0.");
+        self.internal_assert_accepts_donations(id);
+        // Escrow is counted and refunded in NEAR. 
+        require!(
+          self.internal_escrow_campaign(id).is_none(),
+          "This category is in an all-or-nothing campaign, which takes NEAR only."
+        );
+        let beneficiary_id = self.internal_beneficiary(id);
+
+        let (token_id, is_mint) = self.internal_token_for_category(
+          &sender_id,
+          &mut token_id_list,
+          id,
+          &donation.suffix_token_id,
+          donation.issued_at,
+        );
+
+        self.internal_add_ft_donation(&token_id, &ft_contract_id, amount.0);
+
+        if is_mint {
+          minted_token_ids.push(token_id.clone());
+        }
+
+        donations_per_beneficiary
+            .entry(beneficiary_id)
+            .or_insert_with(Vec::new)
+            .push(CategoryDonation {
+              category_id: id,
+              token_id,
+              deposit: amount,
+              is_mint,
+              escrow_campaign_id: None,
+            });
+      }
+
+      self.tokens_per_owner_ordered.insert(&sender_id, &token_id_list);
+
+      let required_storage_in_bytes = env::storage_usage().saturating_sub(initial_storage_usage);
+      self.internal_charge_storage(&sender_id, required_storage_in_bytes);
+
+      if !minted_token_ids.is_empty() {
+        let nft_mint_log: EventLog = EventLog {
+          standard: NFT_STANDARD_NAME.to_string(),
+          version : NFT_METADATA_SPEC.to_string(),
+          event   : EventLogVariant::NftMint(vec![NftMintLog {
+            owner_id : sender_id.to_string(),
+            token_ids: minted_token_ids,
+            memo     : None,
+          }]),
+        };
+
+        env::log_str(&nft_mint_log.to_string());
+      }
+
+      // Forward to each beneficiary. Whatever fails is handed back to 
+      // the fungible token contract as unused, which refunds the sender. 
+      let mut transfers: Vec<BeneficiaryTransfer> = Vec::new();
+      let mut promises: Option<Promise> = None;
+
+      for (beneficiary_id, donations) in donations_per_beneficiary {
+        let amount: u128 = donations.iter().map(|donation| donation.deposit.0).sum();
+        let promise = ext_ft::ft_transfer(
+          beneficiary_id.clone(),
+          U128(amount),
+          Some("Donation".to_string()),
+
+          ft_contract_id.clone(),
+          ONE_YOCTO,
+          GAS_FOR_FT_TRANSFER
+        );
+
+        promises = Some(match promises {
+          Some(promises) => promises.and(promise),
+          None => promise,
+        });
+
+        transfers.push(BeneficiaryTransfer {
+          beneficiary_id,
+          donations,
+        });
+      }
+
+      promises.unwrap().then(
+        ext_self::resolve_ft_donation(
+          sender_id,
+          ft_contract_id,
+          U128(amount.0 - total_donation),
+          transfers,
+
+          env::current_account_id(),
+          0,
+          GAS_FOR_RESOLVE_FT_DONATION
+        )
+      ).into()
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Resolve `ft_on_transfer`: take failed transfers back off the tokens 
+    /// and return them, together with the unused amount, as unused. 
+    #[private]
+    pub fn resolve_ft_donation(
+      &mut self,
+      sender_id: AccountId,
+      ft_contract_id: AccountId,
+      unused_amount: U128,
+      transfers: Vec<BeneficiaryTransfer>,
+    ) -> U128 {
+      let mut unused_amount: u128 = unused_amount.0;
+
+      for (index, transfer) in transfers.into_iter().enumerate() {
+        if let PromiseResult::Successful(_) = env::promise_result(index as u64) {
+          for donation in transfer.donations {
+            self.internal_append_ledger(
+              &sender_id,
+              donation.category_id,
+              &donation.token_id,
+              donation.deposit.0,
+              Some(ft_contract_id.clone()),
+              false,
+            );
+          }
+
+          continue;
+        }
+
+        for donation in transfer.donations {
+          self.internal_remove_ft_donation(&donation.token_id, &ft_contract_id, donation.deposit.0);
+          unused_amount += donation.deposit.0;
+
+          near_sdk::log!(
+            "Returning {} of {} donated by @{} to @{}",
+            donation.deposit.0,
+            ft_contract_id,
+            sender_id,
+            transfer.beneficiary_id
+          );
+        }
+      }
+
+      U128(unused_amount)
+    }
+
+
+    /// Allow donations with this NEP-141 token. Treasurers only. 
+    pub fn add_accepted_ft(&mut self, ft_contract_id: AccountId) {
+      self.internal_assert_role(Role::Treasurer);
+
+      self.accepted_fts.insert(&ft_contract_id);
+    }
+
+
+    /// Stop accepting donations with this NEP-141 token. Treasurers only. 
+    pub fn remove_accepted_ft(&mut self, ft_contract_id: AccountId) {
+      self.internal_assert_role(Role::Treasurer);
+
+      self.accepted_fts.remove(&ft_contract_id);
+    }
+
+
+    pub fn get_accepted_fts(&self) -> Vec<AccountId> {
+      self.accepted_fts.to_vec()
+    }
+
+
+    /// Fungible token donations on this token, keyed by token contract. 
+    pub fn get_ft_donations(&self, token_id: TokenId) -> HashMap<AccountId, U128> {
+      self.ft_donations_by_token_id
+          .get(&token_id)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|(ft_contract_id, amount)| (ft_contract_id, U128(amount)))
+          .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn ft_contract_id() -> AccountId {
+      "mock-ft.near".parse().unwrap()
+    }
+
+    fn donation_msg(amount: &str) -> String {
+      format!(
+        r#"{{"suffix_token_id": "-donor", "hash_of_amounts": {{"0": "{}"}}}}"#,
+        amount
+      )
+    }
+
+    /// Contract accepting the mock token, and a donor with storage balance. 
+    fn setup() -> Contract {
+      let mut contract = setup_contract();
+
+      set_caller(&owner_id(), 0);
+      contract.add_accepted_ft(ft_contract_id());
+
+      set_caller(&donor_id(), ONE_NEAR / 10);
+      contract.deposit_storage(None);
+
+      contract
+    }
+
+
+    #[test]
+    fn donation_mints_token_and_charges_storage() {
+      let mut contract = setup();
+      let storage_balance = contract.get_storage_balance(donor_id()).0;
+
+      set_caller(&ft_contract_id(), 0);
+      contract.ft_on_transfer(donor_id(), U128(100), donation_msg("60"));
+
+      let token = contract.nft_token("animal-donor".to_string()).unwrap();
+      assert_eq!(token.owner_id, donor_id());
+      assert_eq!(
+        contract.get_ft_donations("animal-donor".to_string()).get(&ft_contract_id()),
+        Some(&U128(60))
+      );
+      assert!(contract.get_storage_balance(donor_id()).0 < storage_balance);
+    }
+
+
+    #[test]
+    #[should_panic(expected = "Donation to each category must be more than 0.")]
+    fn zero_allocation_is_rejected() {
+      let mut contract = setup();
+
+      set_caller(&ft_contract_id(), 0);
+      contract.ft_on_transfer(donor_id(), U128(1), donation_msg("0"));
+    }
+
+
+    #[test]
+    #[should_panic(expected = "Please call deposit_storage first.")]
+    fn minting_needs_storage_balance() {
+      let mut contract = setup_contract();
+
+      set_caller(&owner_id(), 0);
+      contract.add_accepted_ft(ft_contract_id());
+
+      set_caller(&ft_contract_id(), 0);
+      contract.ft_on_transfer(donor_id(), U128(100), donation_msg("100"));
+    }
+
+
+    #[test]
+    #[should_panic(expected = "This fungible token is not accepted for donations.")]
+    fn unknown_token_is_rejected() {
+      let mut contract = setup();
+
+      set_caller(&"other-ft.near".parse().unwrap(), 0);
+      contract.ft_on_transfer(donor_id(), U128(100), donation_msg("100"));
+    }
+
+
+    #[test]
+    fn failed_transfer_is_returned_as_unused() {
+      let mut contract = setup();
+
+      set_caller(&ft_contract_id(), 0);
+      contract.ft_on_transfer(donor_id(), U128(100), donation_msg("60"));
+
+      set_callback(&donor_id(), 0, vec![PromiseResult::Failed]);
+      let unused = contract.resolve_ft_donation(
+        donor_id(),
+        ft_contract_id(),
+        U128(40),
+        vec![BeneficiaryTransfer {
+          beneficiary_id: beneficiary_id(),
+          donations: vec![CategoryDonation {
+            category_id: 0,
+            token_id: "animal-donor".to_string(),
+            deposit: U128(60),
+            is_mint: true,
+            escrow_campaign_id: None,
+          }],
+        }],
+      );
+
+      assert_eq!(unused, U128(100));
+      assert_eq!(
+        contract.get_ft_donations("animal-donor".to_string()).get(&ft_contract_id()),
+        Some(&U128(0))
+      );
+      assert_eq!(contract.get_donation_count(), 0);
+    }
+
+
+    #[test]
+    fn successful_transfer_is_recorded() {
+      let mut contract = setup();
+
+      set_caller(&ft_contract_id(), 0);
+      contract.ft_on_transfer(donor_id(), U128(60), donation_msg("60"));
+
+      set_callback(&donor_id(), 0, vec![PromiseResult::Successful(vec![])]);
+      let unused = contract.resolve_ft_donation(
+        donor_id(),
+        ft_contract_id(),
+        U128(0),
+        vec![BeneficiaryTransfer {
+          beneficiary_id: beneficiary_id(),
+          donations: vec![CategoryDonation {
+            category_id: 0,
+            token_id: "animal-donor".to_string(),
+            deposit: U128(60),
+            is_mint: true,
+            escrow_campaign_id: None,
+          }],
+        }],
+      );
+
+      assert_eq!(unused, U128(0));
+
+      let records = contract.get_donations_by_account(donor_id(), None, Some(10));
+      assert_eq!(records.len(), 1);
+      assert_eq!(records[0].amount, U128(60));
+      assert_eq!(records[0].ft_contract_id, Some(ft_contract_id()));
+    }
+}
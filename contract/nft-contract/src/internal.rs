@@ -16,6 +16,15 @@ pub(crate) fn hash_account_id(account_id: &AccountId) -> CryptoHash {
 }
 
 
+/// same as `hash_account_id`, for token IDs. 
+pub(crate) fn hash_token_id(token_id: &TokenId) -> CryptoHash {
+  let mut hash = CryptoHash::default();
+
+  hash.copy_from_slice(&env::sha256(token_id.as_bytes()));
+  hash
+}
+
+
 /// calculate how many bytes the account ID is taking up
 pub(crate) fn bytes_for_approved_account_id(account_id: &AccountId) -> u64 {
   account_id.as_str().len() as u64 
@@ -103,6 +112,268 @@ pub(crate) fn assert_at_least_one_yocto() {
 
 
 impl Contract {
+  /// get the beneficiary of a category; panic if category has none.
+  pub(crate) fn internal_beneficiary(&self, category_id: CategoryId) -> AccountId {
+    expect_lightweight(
+      self.beneficiary_by_cat_id.get(&category_id),
+      "Cannot find beneficiary for this category. Please contact support."
+    )
+  }
+
+  /// bookkeeping for a NEAR donation whose transfer went through. 
+  /// `escrow_campaign_id` is the campaign it was routed to, if any; 
+  /// escrowed donations aren't matched, as the pools would pay out 
+  /// even if the campaign misses its target. 
+  pub(crate) fn internal_record_donation(
+    &mut self,
+    account_id: &AccountId,
+    category_id: CategoryId,
+    token_id: &TokenId,
+    donate_amount: Balance,
+    anonymous: bool,
+    escrow_campaign_id: Option<CampaignId>,
+  ) {
+    if escrow_campaign_id.is_none() {
+      self.internal_draw_matching(category_id, token_id, donate_amount);
+    }
+
+    self.internal_tally_qf(account_id, category_id, donate_amount);
+    let index = self.internal_append_ledger(
+      account_id, 
+      category_id, 
+      token_id, 
+      donate_amount, 
+      None, 
+      anonymous
+    );
+    self.internal_count_campaigns(
+      account_id, 
+      category_id, 
+      donate_amount, 
+      anonymous, 
+      escrow_campaign_id, 
+      index
+    );
+  }
+
+  /// record a donation of `account_id` whose transfer hasn't resolved yet.
+  /// Anonymous ones are kept apart, out of `get_pending_donation`. 
+  pub(crate) fn internal_add_pending_donation(
+    &mut self,
+    account_id: &AccountId,
+    amount: Balance,
+    anonymous: bool,
+  ) {
+    let pending_donations = if anonymous {
+      &mut self.anonymous_pending_donations
+    } else {
+      &mut self.pending_donations
+    };
+
+    let pending = pending_donations.get(account_id).unwrap_or(0);
+    pending_donations.insert(account_id, &(pending + amount));
+  }
+
+  /// a pending donation of `account_id` resolved, either way. 
+  pub(crate) fn internal_remove_pending_donation(
+    &mut self,
+    account_id: &AccountId,
+    amount: Balance,
+    anonymous: bool,
+  ) {
+    let pending_donations = if anonymous {
+      &mut self.anonymous_pending_donations
+    } else {
+      &mut self.pending_donations
+    };
+
+    let pending = pending_donations.get(account_id).unwrap_or(0).saturating_sub(amount);
+
+    if pending == 0 {
+      pending_donations.remove(account_id);
+    } else {
+      pending_donations.insert(account_id, &pending);
+    }
+  }
+
+  /// credit a fungible token donation to a token.
+  pub(crate) fn internal_add_ft_donation(
+    &mut self,
+    token_id: &TokenId,
+    ft_contract_id: &AccountId,
+    amount: Balance,
+  ) {
+    let mut ft_donations = self.ft_donations_by_token_id.get(token_id).unwrap_or_default();
+    *ft_donations.entry(ft_contract_id.clone()).or_insert(0) += amount;
+    self.ft_donations_by_token_id.insert(token_id, &ft_donations);
+  }
+
+  /// take a fungible token donation back off a token.
+  pub(crate) fn internal_remove_ft_donation(
+    &mut self,
+    token_id: &TokenId,
+    ft_contract_id: &AccountId,
+    amount: Balance,
+  ) {
+    let mut ft_donations = self.ft_donations_by_token_id.get(token_id).unwrap_or_default();
+
+    if let Some(donated) = ft_donations.get_mut(ft_contract_id) {
+      *donated = donated.saturating_sub(amount);
+    }
+
+    self.ft_donations_by_token_id.insert(token_id, &ft_donations);
+  }
+
+  /// refund a failed donation to `account_id` and log it so support 
+  /// can reconcile. The log leaves out the donor of `anonymous` donations. 
+  pub(crate) fn internal_refund_donation(
+    &mut self,
+    account_id: &AccountId,
+    token_id: &TokenId,
+    amount: Balance,
+    anonymous: bool,
+    reason: &str,
+  ) {
+    if amount > 0 {
+      Promise::new(account_id.clone()).transfer(amount);
+    }
+
+    let donation_refund_log: EventLog = EventLog {
+      standard: DONATION_STANDARD_NAME.to_string(),
+      version : DONATION_EVENT_VERSION.to_string(),
+      event   : EventLogVariant::DonationRefund(vec![DonationRefundLog {
+        account_id: if anonymous { None } else { Some(account_id.to_string()) },
+        token_id  : token_id.to_string(),
+        amount    : amount.to_string(),
+        reason    : reason.to_string(),
+      }]),
+    };
+
+    env::log_str(&donation_refund_log.to_string());
+  }
+
+  /// create a token with its metadata, category and owner entries. 
+  /// Panics if token already exists. 
+  pub(crate) fn internal_mint(
+    &mut self,
+    token_id: &TokenId,
+    category_id: CategoryId,
+    donate_amount: Balance,
+    metadata: &TokenMetadata,
+    receiver_id: &AccountId,
+    royalty: HashMap<AccountId, u16>,
+  ) {
+    // specify the token struct that contains the owner ID. 
+    let mut token = Token {
+      owner_id: receiver_id.clone(),
+      donate_amount,
+      approved_account_ids: Default::default(),  // default value is empty map.
+      next_approval_id: 1,  // 0 will be inserted later.
+      royalty,
+    };
+
+    // We need contract be approved so it can pull back nft
+    // when user donate the next time, to update it. 
+    token.approved_account_ids.insert(env::current_account_id(), 0);
+
+    // insert token ID and token struct and make sure token
+    // doesn't exist. 
+    require!(
+      self.tokens_by_id.insert(token_id, &token).is_none(),
+      "Token already exists."
+    );
+
+    self.token_metadata_by_id.insert(token_id, metadata);
+    self.category_by_token_id.insert(token_id, &category_id);
+    self.internal_log_tier_change(token_id, category_id, 0, donate_amount);
+
+    self.internal_add_token_to_owner(receiver_id, token_id);
+  }
+
+  /// get the token `account_id` holds for `category_id`, minting one 
+  /// without donation if they don't have it yet. `token_id_list` is their
+  /// ordered map, updated in place. Returns token ID and whether it's minted. 
+  pub(crate) fn internal_token_for_category(
+    &mut self,
+    account_id: &AccountId,
+    token_id_list: &mut HashMap<CategoryId, TokenId>,
+    category_id: CategoryId,
+    suffix_token_id: &str,
+    issued_at: Option<u64>,
+  ) -> (TokenId, bool) {
+    if let Some(token_id) = token_id_list.get(&category_id) {
+      // Due to some errors, we mint again when token cannot be found. 
+      if self.tokens_by_id.get(token_id).is_some() {
+        return (token_id.clone(), false);
+      }
+    }
+
+    let token_id: TokenId = match token_id_list.get(&category_id) {
+      Some(token_id) => self.internal_unique_token_id(token_id.clone()),
+      None => {
+        let prefix: String = expect_lightweight(
+          self.categories.get(category_id as u64),
+          "Cannot find category. Please contact support."
+        );
+
+        self.internal_unique_token_id(prefix + suffix_token_id)
+      }
+    };
+
+    let mut metadata = expect_lightweight(
+      self.token_metadata_by_cat_id.get(&category_id),
+      "Found category but not its metadata. Maybe forgot to map?"
+    );
+
+    metadata.issued_at = issued_at;
+
+    self.internal_mint(
+      &token_id,
+      category_id,
+      0,
+      &metadata,
+      account_id,
+      HashMap::new(),
+    );
+    // paid by the caller, see `internal_batch_donate` and `ft_on_transfer`. 
+    self.storage_payer_by_token_id.insert(&token_id, account_id);
+
+    token_id_list.insert(category_id, token_id.clone());
+
+    (token_id, true)
+  }
+
+  /// remove a token and everything pointing to it, and keep its ID from 
+  /// being minted again. Returns the removed token. Approved account IDs 
+  /// are not refunded, the caller does that. 
+  pub(crate) fn internal_burn_token(&mut self, token_id: &TokenId) -> Token {
+    let token = expect_lightweight(
+      self.tokens_by_id.remove(token_id),
+      "This token_id cannot be found."
+    );
+
+    self.burned_token_ids.insert(token_id);
+    self.storage_payer_by_token_id.remove(token_id);
+    self.token_metadata_by_id.remove(token_id);
+    self.internal_remove_token_from_owner(&token.owner_id, token_id);
+
+    if let Some(category_id) = self.category_by_token_id.remove(token_id) {
+      if let Some(mut token_id_list) = self.tokens_per_owner_ordered.get(&token.owner_id) {
+        if token_id_list.get(&category_id) == Some(token_id) {
+          token_id_list.remove(&category_id);
+
+          if token_id_list.is_empty() {
+            self.tokens_per_owner_ordered.remove(&token.owner_id);
+          } else {
+            self.tokens_per_owner_ordered.insert(&token.owner_id, &token_id_list);
+          }
+        }
+      }
+    }
+
+    token
+  }
+
   /// add a token to the set of tokens an owner has. 
   pub(crate) fn internal_add_token_to_owner(
     &mut self,
@@ -199,10 +470,10 @@ impl Contract {
     // add token to receiver_id's set.
     self.internal_add_token_to_owner(receiver_id, token_id);
 
-    // create new token struct
+    // create new token struct, keeping what was donated on it. 
     let new_token = Token {
       owner_id: receiver_id.clone(),
-      donate_amount: 0.to_string(),
+      donate_amount: token.donate_amount,
       // reset approval account IDs
       approved_account_ids: Default::default(),
       next_approval_id: token.next_approval_id,
@@ -211,6 +482,8 @@ impl Contract {
 
     self.tokens_by_id.insert(token_id, &new_token);  // replace old entry
 
+    self.internal_after_transfer(token_id, &token.owner_id, receiver_id);
+
     // log memo if available
     if let Some(memo) = memo.as_ref() {
       env::log_str(&format!("Memo: {}", memo).to_string());
@@ -0,0 +1,246 @@
+use crate::*;
+
+pub type DonationIndex = u64;
+
+
+/// One donation, as it happened. Never changed once appended. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct DonationRecord {
+    pub token_id: TokenId,
+    pub category_id: CategoryId,
+
+    // who the donation is credited to, and who signed the transaction
+    // (e.g. a keeper executing a pledge). 
+    pub donor_id: AccountId,
+    pub signer_id: AccountId,
+
+    pub amount: Balance,
+    // None for NEAR, otherwise the NEP-141 token contract. 
+    pub ft_contract_id: Option<AccountId>,
+
+    pub timestamp: u64,  // block timestamp, nanoseconds. 
+    pub block_height: u64,
+
+    // donor is hidden from views until they claim the token, 
+    // see `revealed_donations`. 
+    pub anonymous: bool,
+}
+
+/// The Json donation record is what will be returned from view calls. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonDonationRecord {
+    pub index: DonationIndex,
+    pub token_id: TokenId,
+    pub category_id: CategoryId,
+    pub donor_id: Option<AccountId>,  // None if anonymous
+    pub signer_id: Option<AccountId>,
+    pub amount: U128,
+    pub ft_contract_id: Option<AccountId>,
+    pub timestamp: u64,
+    pub block_height: u64,
+    pub anonymous: bool,  // donor still hidden. 
+    pub refunded: bool,  // taken back out of a campaign's escrow. 
+}
+
+
+/// Positions `[start, end)` of a page within `len` entries, so pages 
+/// are read directly instead of iterating over everything before them. 
+fn page_range(from_index: Option<U128>, limit: Option<u64>, len: u64) -> (u64, u64) {
+  let start = std::cmp::min(u128::from(from_index.unwrap_or(U128(0))), len as u128) as u64;
+  let end = std::cmp::min(start.saturating_add(limit.unwrap_or(0)), len);
+
+  (start, end)
+}
+
+
+impl DonationRecord {
+    pub(crate) fn to_json(
+      &self,
+      index: DonationIndex,
+      refunded: bool,
+      revealed: bool,
+    ) -> JsonDonationRecord {
+      let anonymous = self.anonymous && !revealed;
+
+      JsonDonationRecord {
+        index,
+        token_id: self.token_id.clone(),
+        category_id: self.category_id,
+        donor_id: if anonymous { None } else { Some(self.donor_id.clone()) },
+        signer_id: if anonymous { None } else { Some(self.signer_id.clone()) },
+        amount: U128(self.amount),
+        ft_contract_id: self.ft_contract_id.clone(),
+        timestamp: self.timestamp,
+        block_height: self.block_height,
+        anonymous,
+        refunded,
+      }
+    }
+}
+
+
+impl Contract {
+    /// Append a donation to the ledger and its per account, 
+    /// per category and per token indexes. Anonymous donations 
+    /// stay out of the per account index until revealed. 
+    /// Returns its position in the ledger. 
+    pub(crate) fn internal_append_ledger(
+      &mut self,
+      donor_id: &AccountId,
+      category_id: CategoryId,
+      token_id: &TokenId,
+      amount: Balance,
+      ft_contract_id: Option<AccountId>,
+      anonymous: bool,
+    ) -> DonationIndex {
+      let index: DonationIndex = self.donation_ledger.len();
+
+      self.donation_ledger.push(&DonationRecord {
+        token_id: token_id.clone(),
+        category_id,
+        donor_id: donor_id.clone(),
+        signer_id: env::signer_account_id(),
+        amount,
+        ft_contract_id,
+        timestamp: env::block_timestamp(),
+        block_height: env::block_height(),
+        anonymous,
+      });
+
+      if !anonymous {
+        self.internal_index_ledger_by_account(donor_id, index);
+      }
+
+      let mut by_category = self.ledger_by_cat_id.get(&category_id).unwrap_or_else(|| {
+        Vector::new(
+          StorageKey::LedgerByCatIdInner { category_id }
+          .try_to_vec()
+          .unwrap(),
+        )
+      });
+      by_category.push(&index);
+      self.ledger_by_cat_id.insert(&category_id, &by_category);
+
+      let mut by_token = self.ledger_by_token_id.get(token_id).unwrap_or_else(|| {
+        Vector::new(
+          StorageKey::LedgerByTokenIdInner {
+            token_id_hash: hash_token_id(token_id),
+          }
+          .try_to_vec()
+          .unwrap(),
+        )
+      });
+      by_token.push(&index);
+      self.ledger_by_token_id.insert(token_id, &by_token);
+
+      index
+    }
+
+    pub(crate) fn internal_index_ledger_by_account(
+      &mut self,
+      account_id: &AccountId,
+      index: DonationIndex,
+    ) {
+      let mut by_account = self.ledger_by_account.get(account_id).unwrap_or_else(|| {
+        Vector::new(
+          StorageKey::LedgerByAccountInner {
+            account_id_hash: hash_account_id(account_id),
+          }
+          .try_to_vec()
+          .unwrap(),
+        )
+      });
+      by_account.push(&index);
+      self.ledger_by_account.insert(account_id, &by_account);
+    }
+
+    /// The record at `index` of the ledger, for views. 
+    pub(crate) fn internal_ledger_json(&self, index: DonationIndex) -> JsonDonationRecord {
+      self.donation_ledger
+          .get(index)
+          .unwrap()
+          .to_json(
+            index,
+            self.refunded_donations.contains(&index),
+            self.revealed_donations.contains(&index),
+          )
+    }
+
+    /// Records of an index, paginated. 
+    fn internal_ledger_page(
+      &self,
+      indexes: Option<Vector<DonationIndex>>,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonDonationRecord> {
+      let indexes = if let Some(indexes) = indexes {
+        indexes
+      } else {
+        return vec![];
+      };
+
+      let (start, end) = page_range(from_index, limit, indexes.len());
+
+      (start..end)
+          .map(|position| self.internal_ledger_json(indexes.get(position).unwrap()))
+          .collect()
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Number of donations ever made. 
+    pub fn get_donation_count(&self) -> u64 {
+      self.donation_ledger.len()
+    }
+
+
+    /// Every donation, oldest first. 
+    pub fn get_donations(
+      &self,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonDonationRecord> {
+      let (start, end) = page_range(from_index, limit, self.donation_ledger.len());
+
+      (start..end)
+          .map(|index| self.internal_ledger_json(index))
+          .collect()
+    }
+
+
+    /// Donations credited to an account, oldest first. 
+    pub fn get_donations_by_account(
+      &self,
+      account_id: AccountId,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonDonationRecord> {
+      self.internal_ledger_page(self.ledger_by_account.get(&account_id), from_index, limit)
+    }
+
+
+    /// Donations to a category, oldest first. 
+    pub fn get_donations_by_category(
+      &self,
+      category_id: CategoryKey,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonDonationRecord> {
+      let category_id = self.internal_category_id(&category_id);
+      self.internal_ledger_page(self.ledger_by_cat_id.get(&category_id), from_index, limit)
+    }
+
+
+    /// Donations made on a token, oldest first. 
+    pub fn get_donations_by_token(
+      &self,
+      token_id: TokenId,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonDonationRecord> {
+      self.internal_ledger_page(self.ledger_by_token_id.get(&token_id), from_index, limit)
+    }
+}
@@ -1,40 +1,76 @@
 use std::collections::HashMap;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::{Base64VecU8, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     env, near_bindgen, AccountId, Balance, CryptoHash, 
-    PanicOnDefault, Promise, PromiseOrValue, ext_contract, Gas
+    PanicOnDefault, Promise, PromiseOrValue, PromiseResult, ext_contract, Gas
 };
 use near_sdk::utils::is_promise_success;
 
-use near_helper::{near_to_yoctonear, expect_lightweight, yoctonear_to_near};
+use near_helper::{near_to_yoctonear, expect_lightweight};
 
 use crate::internal::*;
+use crate::migrate::write_state_version;
 pub use crate::metadata::*;
 pub use crate::mint::*;
 pub use crate::nft_core::*;
 pub use crate::approval::*;
 pub use crate::royalty::*;
 pub use crate::events::*;
-
+pub use crate::campaign::*;
+pub use crate::category::*;
+pub use crate::ft_receiver::*;
+pub use crate::ledger::*;
+pub use crate::statement::*;
+pub use crate::matching::*;
+pub use crate::pause::*;
+pub use crate::pledge::*;
+pub use crate::quadratic::*;
+pub use crate::roles::*;
+pub use crate::tier::*;
+pub use crate::transfer::*;
+
+mod anonymous; 
 mod approval; 
+mod burn; 
+mod campaign; 
+mod category; 
 mod enumeration; 
+mod ft_receiver; 
 mod internal;
+mod ledger; 
+mod matching; 
 mod metadata; 
+mod migrate; 
 mod mint; 
 mod nft_core; 
-mod royalty; 
+mod ownership; 
+mod pause; 
+mod pledge; 
+mod quadratic; 
+mod roles; 
+mod royalty;
+mod soulbound; 
+mod statement; 
+mod storage; 
+mod tier; 
+mod transfer; 
 mod events;
 
+#[cfg(test)]
+mod testing;
+
 pub const NFT_METADATA_SPEC: &str = "1.0.0";
 pub const NFT_STANDARD_NAME: &str = "nep171";
+pub const DONATION_STANDARD_NAME: &str = "ea_donation";
+pub const DONATION_EVENT_VERSION: &str = "1.0.0";
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
-    // Contract owner
+    // Contract owner, only changed by `propose_owner` then `accept_ownership`.
     pub owner_id: AccountId,
 
     // Keeps track of all the token IDs for a given account
@@ -59,6 +95,137 @@ pub struct Contract {
 
     /// List of token metadata creator
     pub token_metadata_by_cat_id: UnorderedMap<CategoryId, TokenMetadata>,
+
+    /// Account that receives the donations of each category. 
+    pub beneficiary_by_cat_id: LookupMap<CategoryId, AccountId>,
+
+    /// Which category a token is minted for, so subsequent donations
+    /// to the token know where to go. 
+    pub category_by_token_id: LookupMap<TokenId, CategoryId>,
+
+    /// Donations in flight (transfer not yet resolved) per signer, 
+    /// in yoctoNEAR. Refunded if the transfer fails. 
+    pub pending_donations: LookupMap<AccountId, Balance>,
+
+    /// NEP-141 token contracts we accept donations in. 
+    pub accepted_fts: UnorderedSet<AccountId>,
+
+    /// Fungible token donations of each token, per token contract. 
+    pub ft_donations_by_token_id: LookupMap<TokenId, HashMap<AccountId, Balance>>,
+
+    /// Sponsored matching pools, pool ID is the position. 
+    pub matching_pools: Vector<MatchingPool>,
+
+    /// Open matching pools of each category. 
+    pub matching_pools_by_cat_id: LookupMap<CategoryId, Vec<PoolId>>,
+
+    /// Total matched on top of each token's donations. 
+    pub matched_by_token_id: LookupMap<TokenId, Balance>,
+
+    /// Quadratic funding rounds, round ID is the position. 
+    pub qf_rounds: Vector<QfRound>,
+
+    /// Unfinalized rounds of each category. 
+    pub qf_rounds_by_cat_id: LookupMap<CategoryId, Vec<RoundId>>,
+
+    /// Running totals of each category in a round. 
+    pub qf_tallies: LookupMap<(RoundId, CategoryId), QfTally>,
+
+    /// What each donor gave to a category in a round. 
+    pub qf_contributions: LookupMap<(RoundId, CategoryId, AccountId), Balance>,
+
+    /// Recurring donation pledge of each account. 
+    pub pledges: UnorderedMap<AccountId, Pledge>,
+
+    /// Where `execute_due_pledges` continues from. 
+    pub pledge_cursor: u64,
+
+    /// Fundraising campaigns, campaign ID is the position. 
+    pub campaigns: Vector<Campaign>,
+
+    /// Campaigns of each category. 
+    pub campaigns_by_cat_id: LookupMap<CategoryId, Vec<CampaignId>>,
+
+    /// What each donor gave to a campaign. 
+    pub campaign_donations: LookupMap<(CampaignId, AccountId), Balance>,
+
+    /// Append-only record of every donation. 
+    pub donation_ledger: Vector<DonationRecord>,
+
+    /// Positions in `donation_ledger` of each donor's donations. 
+    pub ledger_by_account: LookupMap<AccountId, Vector<DonationIndex>>,
+
+    /// Positions in `donation_ledger` of each category's donations. 
+    pub ledger_by_cat_id: LookupMap<CategoryId, Vector<DonationIndex>>,
+
+    /// Positions in `donation_ledger` of each token's donations. 
+    pub ledger_by_token_id: LookupMap<TokenId, Vector<DonationIndex>>,
+
+    /// Donor of each token the contract holds for an anonymous donation. 
+    pub anonymous_donor_by_token_id: LookupMap<TokenId, AccountId>,
+
+    /// Like `tokens_per_owner_ordered`, for anonymous tokens, by donor. 
+    pub anonymous_tokens_per_donor: LookupMap<AccountId, HashMap<CategoryId, TokenId>>,
+
+    /// Accounts granted each role, besides the owner. 
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+
+    /// Categories that no longer take donations. 
+    pub archived_categories: UnorderedSet<CategoryId>,
+
+    /// Id of each category by its slug, the `Category` it was generated with. 
+    pub category_id_by_slug: LookupMap<Category, CategoryId>,
+
+    /// Emergency switches, see `pause`. 
+    pub pause_state: PauseState,
+
+    /// Proposed owner, until they accept. 
+    pub pending_owner_id: Option<AccountId>,
+
+    /// Whether every token is soulbound (not transferable). 
+    pub soulbound: bool,
+
+    /// Categories whose tokens are soulbound. 
+    pub soulbound_categories: UnorderedSet<CategoryId>,
+
+    /// Who keeps ledger credit when a token is transferred. 
+    pub transfer_credit_policy: TransferCreditPolicy,
+
+    /// Metadata tiers of each category, by ascending threshold. 
+    pub tiers_by_cat_id: LookupMap<CategoryId, Vec<MetadataTier>>,
+
+    /// NEAR deposited by each account for storage it doesn't attach 
+    /// NEAR for, e.g. tokens minted by fungible token donations. 
+    pub storage_balances: LookupMap<AccountId, Balance>,
+
+    /// Positions in `donation_ledger` of what each donor has in a 
+    /// campaign's escrow. 
+    pub escrowed_donations: LookupMap<(CampaignId, AccountId), Vec<DonationIndex>>,
+
+    /// Positions in `donation_ledger` of donations refunded from escrow. 
+    pub refunded_donations: LookupSet<DonationIndex>,
+
+    /// Positions in `donation_ledger` of anonymous donations revealed 
+    /// by their donor claiming the token. 
+    pub revealed_donations: LookupSet<DonationIndex>,
+
+    /// Like `pending_donations`, for anonymous donations, with no view on it. 
+    pub anonymous_pending_donations: LookupMap<AccountId, Balance>,
+
+    /// Like `campaign_donations`, for anonymous donations, with no view on it. 
+    pub anonymous_campaign_donations: LookupMap<(CampaignId, AccountId), Balance>,
+
+    /// Who paid for the storage of each token, refunded to them when it's 
+    /// burned. None for tokens the contract paid for. 
+    pub storage_payer_by_token_id: LookupMap<TokenId, AccountId>,
+
+    /// Burned token IDs, never minted again: the ledger, matching and 
+    /// fungible token donations still refer to them. 
+    pub burned_token_ids: LookupSet<TokenId>,
+
+    /// NEAR donate amounts of the tokens each account burned, by category, 
+    /// still counted in `get_owner_donation`. 
+    pub burned_donations: LookupMap<AccountId, HashMap<CategoryId, Balance>>,
 }
 
 /// Helper structure for keys of the persistent collections.
@@ -75,6 +242,47 @@ pub enum StorageKey {
     Categories,
     TokensOrdered,
     MetadataCatId,
+    BeneficiaryByCatId,
+    CategoryByTokenId,
+    PendingDonations,
+    AcceptedFts,
+    FtDonationsByTokenId,
+    MatchingPools,
+    MatchingPoolsByCatId,
+    MatchedByTokenId,
+    QfRounds,
+    QfRoundsByCatId,
+    QfTallies,
+    QfContributions,
+    Pledges,
+    Campaigns,
+    CampaignsByCatId,
+    CampaignDonations,
+    DonationLedger,
+    LedgerByAccount,
+    LedgerByAccountInner { account_id_hash: CryptoHash },
+    LedgerByCatId,
+    LedgerByCatIdInner { category_id: CategoryId },
+    LedgerByTokenId,
+    LedgerByTokenIdInner { token_id_hash: CryptoHash },
+    AnonymousDonorByTokenId,
+    AnonymousTokensPerDonor,
+    RoleMembers,
+    RoleMembersInner { role: Role },
+    ArchivedCategories,
+    CategoryIdBySlug,
+    StateVersion,
+    SoulboundCategories,
+    TiersByCatId,
+    StorageBalances,
+    EscrowedDonations,
+    RefundedDonations,
+    RevealedDonations,
+    AnonymousPendingDonations,
+    AnonymousCampaignDonations,
+    StoragePayerByTokenId,
+    BurnedTokenIds,
+    BurnedDonations,
 }
 
 #[near_bindgen]
@@ -135,37 +343,158 @@ impl Contract {
           token_metadata_by_cat_id: UnorderedMap::new(
             StorageKey::MetadataCatId.try_to_vec().unwrap()
           ),
+
+          beneficiary_by_cat_id: LookupMap::new(
+            StorageKey::BeneficiaryByCatId.try_to_vec().unwrap()
+          ),
+          category_by_token_id: LookupMap::new(
+            StorageKey::CategoryByTokenId.try_to_vec().unwrap()
+          ),
+          pending_donations: LookupMap::new(
+            StorageKey::PendingDonations.try_to_vec().unwrap()
+          ),
+          accepted_fts: UnorderedSet::new(
+            StorageKey::AcceptedFts.try_to_vec().unwrap()
+          ),
+          ft_donations_by_token_id: LookupMap::new(
+            StorageKey::FtDonationsByTokenId.try_to_vec().unwrap()
+          ),
+          matching_pools: Vector::new(
+            StorageKey::MatchingPools.try_to_vec().unwrap()
+          ),
+          matching_pools_by_cat_id: LookupMap::new(
+            StorageKey::MatchingPoolsByCatId.try_to_vec().unwrap()
+          ),
+          matched_by_token_id: LookupMap::new(
+            StorageKey::MatchedByTokenId.try_to_vec().unwrap()
+          ),
+          qf_rounds: Vector::new(
+            StorageKey::QfRounds.try_to_vec().unwrap()
+          ),
+          qf_rounds_by_cat_id: LookupMap::new(
+            StorageKey::QfRoundsByCatId.try_to_vec().unwrap()
+          ),
+          qf_tallies: LookupMap::new(
+            StorageKey::QfTallies.try_to_vec().unwrap()
+          ),
+          qf_contributions: LookupMap::new(
+            StorageKey::QfContributions.try_to_vec().unwrap()
+          ),
+          pledges: UnorderedMap::new(
+            StorageKey::Pledges.try_to_vec().unwrap()
+          ),
+          pledge_cursor: 0,
+          campaigns: Vector::new(
+            StorageKey::Campaigns.try_to_vec().unwrap()
+          ),
+          campaigns_by_cat_id: LookupMap::new(
+            StorageKey::CampaignsByCatId.try_to_vec().unwrap()
+          ),
+          campaign_donations: LookupMap::new(
+            StorageKey::CampaignDonations.try_to_vec().unwrap()
+          ),
+          donation_ledger: Vector::new(
+            StorageKey::DonationLedger.try_to_vec().unwrap()
+          ),
+          ledger_by_account: LookupMap::new(
+            StorageKey::LedgerByAccount.try_to_vec().unwrap()
+          ),
+          ledger_by_cat_id: LookupMap::new(
+            StorageKey::LedgerByCatId.try_to_vec().unwrap()
+          ),
+          ledger_by_token_id: LookupMap::new(
+            StorageKey::LedgerByTokenId.try_to_vec().unwrap()
+          ),
+          anonymous_donor_by_token_id: LookupMap::new(
+            StorageKey::AnonymousDonorByTokenId.try_to_vec().unwrap()
+          ),
+          anonymous_tokens_per_donor: LookupMap::new(
+            StorageKey::AnonymousTokensPerDonor.try_to_vec().unwrap()
+          ),
+          role_members: LookupMap::new(
+            StorageKey::RoleMembers.try_to_vec().unwrap()
+          ),
+          archived_categories: UnorderedSet::new(
+            StorageKey::ArchivedCategories.try_to_vec().unwrap()
+          ),
+          category_id_by_slug: LookupMap::new(
+            StorageKey::CategoryIdBySlug.try_to_vec().unwrap()
+          ),
+          pause_state: PauseState::default(),
+          pending_owner_id: None,
+          soulbound: false,
+          soulbound_categories: UnorderedSet::new(
+            StorageKey::SoulboundCategories.try_to_vec().unwrap()
+          ),
+          transfer_credit_policy: TransferCreditPolicy::StaysWithDonor,
+          tiers_by_cat_id: LookupMap::new(
+            StorageKey::TiersByCatId.try_to_vec().unwrap()
+          ),
+          storage_balances: LookupMap::new(
+            StorageKey::StorageBalances.try_to_vec().unwrap()
+          ),
+          escrowed_donations: LookupMap::new(
+            StorageKey::EscrowedDonations.try_to_vec().unwrap()
+          ),
+          refunded_donations: LookupSet::new(
+            StorageKey::RefundedDonations.try_to_vec().unwrap()
+          ),
+          revealed_donations: LookupSet::new(
+            StorageKey::RevealedDonations.try_to_vec().unwrap()
+          ),
+          anonymous_pending_donations: LookupMap::new(
+            StorageKey::AnonymousPendingDonations.try_to_vec().unwrap()
+          ),
+          anonymous_campaign_donations: LookupMap::new(
+            StorageKey::AnonymousCampaignDonations.try_to_vec().unwrap()
+          ),
+          storage_payer_by_token_id: LookupMap::new(
+            StorageKey::StoragePayerByTokenId.try_to_vec().unwrap()
+          ),
+          burned_token_ids: LookupSet::new(
+            StorageKey::BurnedTokenIds.try_to_vec().unwrap()
+          ),
+          burned_donations: LookupMap::new(
+            StorageKey::BurnedDonations.try_to_vec().unwrap()
+          ),
         };
 
+        write_state_version();
+
         // return the contract object
         this
     }
 
 
-    pub fn get_owner_donation(&self, account_id: AccountId) -> HashMap<String, String> {
-      if let Some(hashmap) = self.tokens_per_owner_ordered.get(&account_id) {
-        let mut return_map = HashMap::new();
+    /// Donate amount per category title of the tokens this account holds, 
+    /// plus those of tokens it burned. 
+    pub fn get_owner_donation(&self, account_id: AccountId) -> HashMap<String, U128> {
+      let mut amounts = self.burned_donations.get(&account_id).unwrap_or_default();
 
+      if let Some(hashmap) = self.tokens_per_owner_ordered.get(&account_id) {
         for (id, token_id) in hashmap {
-          let metadata = expect_lightweight(
-            self.token_metadata_by_cat_id.get(&id),
-            "Found category but not its metadata. Maybe forgot to map?"
-          );
-          let title = metadata.title.unwrap();
-
           let token = expect_lightweight(
             self.tokens_by_id.get(&token_id),
             "Cannot find this token in library"
           );
-          let donate_amount = token.donate_amount;
 
-          return_map.insert(title, donate_amount);
+          *amounts.entry(id).or_insert(0) += token.donate_amount;
         }
+      }
+
+      let mut return_map = HashMap::new();
+
+      for (id, amount) in amounts {
+        let metadata = expect_lightweight(
+          self.token_metadata_by_cat_id.get(&id),
+          "Found category but not its metadata. Maybe forgot to map?"
+        );
+        let title = metadata.title.unwrap();
 
-        return_map  // donate amount stringified.
-      } else {
-        HashMap::new()
+        return_map.insert(title, U128(amount));
       }
+
+      return_map  // donate amount in yoctoNEAR.
     }
 
 
@@ -179,10 +508,13 @@ impl Contract {
     }
 
 
+    /// Categories taking donations; archived ones are left out. 
     pub fn get_list_to_donate(&self) -> HashMap<String, String> {
       let mut temp = HashMap::new();
 
       for (k, v) in self.token_metadata_by_cat_id.iter() {
+        if self.internal_is_archived(k) { continue; }
+
         let id = self.categories.get(k as u64).unwrap();
         let title = v.title.unwrap();
         temp.insert(id, title);
@@ -192,14 +524,47 @@ impl Contract {
     }
 
 
+    /// Same categories as `get_list_to_donate`. 
     pub fn get_id_by_category(&self) -> HashMap<String, u16> {
       let mut temp = HashMap::new();
 
       for (k, _v) in self.token_metadata_by_cat_id.iter() {
+        if self.internal_is_archived(k) { continue; }
+
         let id = self.categories.get(k.clone() as u64).unwrap();
         temp.insert(id, k);
       }
 
       temp
     }
+
+
+    /// Who receives the donations made to this category. 
+    pub fn get_beneficiary(&self, category_id: CategoryKey) -> Option<AccountId> {
+      self.internal_find_category_id(&category_id)
+          .and_then(|category_id| self.beneficiary_by_cat_id.get(&category_id))
+    }
+
+
+    /// Beneficiary of each category, keyed by category (like `get_list_to_donate`).
+    pub fn get_beneficiaries(&self) -> HashMap<String, AccountId> {
+      let mut temp = HashMap::new();
+
+      for k in self.token_metadata_by_cat_id.keys() {
+        let id = self.categories.get(k as u64).unwrap();
+
+        if let Some(beneficiary_id) = self.beneficiary_by_cat_id.get(&k) {
+          temp.insert(id, beneficiary_id);
+        }
+      }
+
+      temp
+    }
+
+
+    /// Donations of this account still waiting for their transfer to resolve. 
+    /// Anonymous ones aren't included. 
+    pub fn get_pending_donation(&self, account_id: AccountId) -> U128 {
+      U128(self.pending_donations.get(&account_id).unwrap_or(0))
+    }
 }
\ No newline at end of file
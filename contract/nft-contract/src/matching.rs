@@ -0,0 +1,230 @@
+use crate::*;
+use near_sdk::require;
+
+pub type PoolId = u64;
+
+/// Ratios are in basis points: 10000 matches 1:1. 
+const RATIO_DENOMINATOR: u128 = 10_000;
+
+
+/// A sponsor's pool matching donations to some categories. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MatchingPool {
+    pub sponsor_id: AccountId,
+    pub category_ids: Vec<CategoryId>,
+
+    // matched per donated yoctoNEAR, in basis points. 
+    pub ratio: u32,
+    pub cap: Balance,
+
+    // what's left to match with. 
+    pub balance: Balance,
+
+    // block timestamp (nanoseconds) after which pool stops matching. 
+    pub expires_at: u64,
+    pub closed: bool,
+}
+
+/// The Json matching pool is what will be returned from view calls. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonMatchingPool {
+    pub pool_id: PoolId,
+    pub sponsor_id: AccountId,
+    pub category_ids: Vec<CategoryId>,
+    pub ratio: u32,
+    pub cap: U128,
+    pub balance: U128,
+    pub expires_at: u64,
+    pub closed: bool,
+}
+
+
+impl MatchingPool {
+    fn to_json(&self, pool_id: PoolId) -> JsonMatchingPool {
+      JsonMatchingPool {
+        pool_id,
+        sponsor_id: self.sponsor_id.clone(),
+        category_ids: self.category_ids.clone(),
+        ratio: self.ratio,
+        cap: U128(self.cap),
+        balance: U128(self.balance),
+        expires_at: self.expires_at,
+        closed: self.closed,
+      }
+    }
+
+    fn is_active(&self) -> bool {
+      !self.closed && self.balance > 0 && env::block_timestamp() < self.expires_at
+    }
+}
+
+
+impl Contract {
+    /// Draw matching for a successful donation from every active pool of 
+    /// its category, send it to the beneficiary and record it on the token. 
+    pub(crate) fn internal_draw_matching(
+      &mut self,
+      category_id: CategoryId,
+      token_id: &TokenId,
+      donate_amount: Balance,
+    ) {
+      let pool_ids = self.matching_pools_by_cat_id.get(&category_id).unwrap_or_default();
+      let mut total_matched: Balance = 0;
+
+      for pool_id in pool_ids {
+        let mut pool = self.matching_pools.get(pool_id).unwrap();
+
+        if !pool.is_active() {
+          continue;
+        }
+
+        let matched = std::cmp::min(
+          donate_amount * pool.ratio as u128 / RATIO_DENOMINATOR,
+          pool.balance
+        );
+
+        pool.balance -= matched;
+        total_matched += matched;
+
+        self.matching_pools.replace(pool_id, &pool);
+      }
+
+      if total_matched == 0 {
+        return;
+      }
+
+      let matched = self.matched_by_token_id.get(token_id).unwrap_or(0);
+      self.matched_by_token_id.insert(token_id, &(matched + total_matched));
+
+      Promise::new(self.internal_beneficiary(category_id)).transfer(total_matched);
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Sponsor a pool matching donations to `category_ids` at `ratio` 
+    /// (basis points, 10000 = 1:1), up to `cap` in total, until `expires_at` 
+    /// (block timestamp in nanoseconds). Attach `cap` plus storage. 
+    #[payable]
+    pub fn create_matching_pool(
+      &mut self,
+      category_ids: Vec<CategoryKey>,
+      ratio: u32,
+      cap: U128,
+      expires_at: u64,
+    ) -> PoolId {
+      let initial_storage_usage = env::storage_usage();
+
+      require!(!category_ids.is_empty(), "Pool must match at least one category.");
+      let category_ids = self.internal_category_ids(category_ids);
+      require!(ratio > 0, "Ratio must be more than 0.");
+      require!(cap.0 > 0, "Cap must be more than 0.");
+      require!(
+        expires_at > env::block_timestamp(),
+        "Pool must expire in the future."
+      );
+
+      for category_id in category_ids.iter() {
+        require!(
+          self.beneficiary_by_cat_id.get(category_id).is_some(),
+          "Cannot find category. Please contact support."
+        );
+      }
+
+      let pool_id = self.matching_pools.len();
+
+      self.matching_pools.push(&MatchingPool {
+        sponsor_id: env::predecessor_account_id(),
+        category_ids: category_ids.clone(),
+        ratio,
+        cap: cap.0,
+        balance: cap.0,
+        expires_at,
+        closed: false,
+      });
+
+      for category_id in category_ids {
+        let mut pool_ids = self.matching_pools_by_cat_id.get(&category_id).unwrap_or_default();
+        pool_ids.push(pool_id);
+        self.matching_pools_by_cat_id.insert(&category_id, &pool_ids);
+      }
+
+      let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(required_storage_in_bytes);
+
+      require!(
+        env::attached_deposit() >= cap.0 + required_cost_to_store_info,
+        format!(
+          "Must attach {} yoctoNEAR to cover cap and storage",
+          cap.0 + required_cost_to_store_info
+        )
+      );
+
+      let refund = env::attached_deposit() - cap.0 - required_cost_to_store_info;
+
+      if refund > 1 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+      }
+
+      pool_id
+    }
+
+
+    /// Close an expired pool and refund its unused balance to the sponsor. 
+    /// Anyone can call this once the pool expired. 
+    pub fn close_matching_pool(&mut self, pool_id: PoolId) {
+      let mut pool = expect_lightweight(
+        self.matching_pools.get(pool_id),
+        "Cannot find this matching pool."
+      );
+
+      require!(!pool.closed, "Matching pool already closed.");
+      require!(
+        env::block_timestamp() >= pool.expires_at,
+        "Matching pool hasn't expired yet."
+      );
+
+      for category_id in pool.category_ids.iter() {
+        let mut pool_ids = self.matching_pools_by_cat_id.get(category_id).unwrap_or_default();
+        pool_ids.retain(|id| *id != pool_id);
+        self.matching_pools_by_cat_id.insert(category_id, &pool_ids);
+      }
+
+      let refund = pool.balance;
+
+      pool.balance = 0;
+      pool.closed = true;
+      self.matching_pools.replace(pool_id, &pool);
+
+      if refund > 0 {
+        Promise::new(pool.sponsor_id).transfer(refund);
+      }
+    }
+
+
+    pub fn get_matching_pool(&self, pool_id: PoolId) -> Option<JsonMatchingPool> {
+      self.matching_pools.get(pool_id).map(|pool| pool.to_json(pool_id))
+    }
+
+
+    /// Open pools matching this category, expired ones included until closed. 
+    pub fn get_matching_pools_for_category(&self, category_id: CategoryKey) -> Vec<JsonMatchingPool> {
+      let category_id = self.internal_category_id(&category_id);
+
+      self.matching_pools_by_cat_id
+          .get(&category_id)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|pool_id| self.matching_pools.get(pool_id).unwrap().to_json(pool_id))
+          .collect()
+    }
+
+
+    /// How much was matched on top of this token's donations. 
+    pub fn get_matched_amount(&self, token_id: TokenId) -> U128 {
+      U128(self.matched_by_token_id.get(&token_id).unwrap_or(0))
+    }
+}
@@ -45,8 +45,8 @@ pub struct Token {
     // owner of the token. 
     pub owner_id: AccountId,
 
-    // donation amount
-    pub donate_amount: String,
+    // donation amount in yoctoNEAR
+    pub donate_amount: Balance,
 
     // list of approved account IDs that have access to transfer the token. 
     // This maps an account ID to an approval ID. 
@@ -64,7 +64,7 @@ pub struct Token {
 pub struct JsonToken {
     pub owner_id: AccountId, 
     pub token_id: TokenId,
-    pub donate_amount: String,
+    pub donate_amount: U128,
     pub metadata: TokenMetadata,
     pub approved_account_ids: HashMap<AccountId, u64>,
     pub royalty: HashMap<AccountId, u16>,
@@ -0,0 +1,742 @@
+use crate::*;
+use near_sdk::require;
+
+const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+const YOCTO_DECIMALS: usize = 24;
+
+/// Kept by `upgrade` for itself; the rest goes to `migrate`. 
+const GAS_FOR_UPGRADE: Gas = Gas(30_000_000_000_000);
+
+
+/// Layout of the contract state. Stored apart from the state, under 
+/// `StorageKey::StateVersion`, so we know how to read the state before 
+/// reading it. Add a version (and keep the old layout around to migrate 
+/// from) whenever `Contract` changes after a release. 
+#[derive(BorshDeserialize, BorshSerialize, Clone, Copy, PartialEq)]
+pub enum StateVersion {
+    /// `OldContract`: stringified donate amounts, no beneficiaries. 
+    /// Deployed before versioning, so it has no version stored. 
+    V1,
+    /// `V2Contract`: before ownership transfer, soulbound tokens, tiers, 
+    /// storage balances, and the escrow, reveal and burn bookkeeping. 
+    V2,
+    /// `Contract`. 
+    V3,
+}
+
+pub const CURRENT_STATE_VERSION: StateVersion = StateVersion::V3;
+
+
+/// Contract state in any of its layouts. 
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedContract {
+    V1(OldContract),
+    V2(V2Contract),
+    V3(Contract),
+}
+
+
+/// Token as stored before donation amounts were kept in yoctoNEAR. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldToken {
+    pub owner_id: AccountId,
+    pub donate_amount: String,
+    pub approved_account_ids: HashMap<AccountId, u64>,
+    pub next_approval_id: u64,
+    pub royalty: HashMap<AccountId, u16>,
+}
+
+
+/// Contract layout before donation amounts were kept in yoctoNEAR 
+/// and before categories had beneficiaries. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldContract {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, OldToken>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub categories: Vector<Category>,
+    pub tokens_per_owner_ordered: LookupMap<AccountId, HashMap<CategoryId, TokenId>>,
+    pub token_metadata_by_cat_id: UnorderedMap<CategoryId, TokenMetadata>,
+}
+
+
+/// Campaign as stored before campaigns could be closed, and before 
+/// refunds settled their outcome. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct V2Campaign {
+    pub category_id: CategoryId,
+    pub title: String,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub target: Balance,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub after_deadline: AfterDeadline,
+    pub mode: CampaignMode,
+    pub raised: Balance,
+    pub donors: u64,
+    pub escrow_released: bool,
+}
+
+
+/// Contract layout of V2, see `StateVersion`. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct V2Contract {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub categories: Vector<Category>,
+    pub tokens_per_owner_ordered: LookupMap<AccountId, HashMap<CategoryId, TokenId>>,
+    pub token_metadata_by_cat_id: UnorderedMap<CategoryId, TokenMetadata>,
+    pub beneficiary_by_cat_id: LookupMap<CategoryId, AccountId>,
+    pub category_by_token_id: LookupMap<TokenId, CategoryId>,
+    pub pending_donations: LookupMap<AccountId, Balance>,
+    pub accepted_fts: UnorderedSet<AccountId>,
+    pub ft_donations_by_token_id: LookupMap<TokenId, HashMap<AccountId, Balance>>,
+    pub matching_pools: Vector<MatchingPool>,
+    pub matching_pools_by_cat_id: LookupMap<CategoryId, Vec<PoolId>>,
+    pub matched_by_token_id: LookupMap<TokenId, Balance>,
+    pub qf_rounds: Vector<QfRound>,
+    pub qf_rounds_by_cat_id: LookupMap<CategoryId, Vec<RoundId>>,
+    pub qf_tallies: LookupMap<(RoundId, CategoryId), QfTally>,
+    pub qf_contributions: LookupMap<(RoundId, CategoryId, AccountId), Balance>,
+    pub pledges: UnorderedMap<AccountId, Pledge>,
+    pub pledge_cursor: u64,
+    pub campaigns: Vector<V2Campaign>,
+    pub campaigns_by_cat_id: LookupMap<CategoryId, Vec<CampaignId>>,
+    pub campaign_donations: LookupMap<(CampaignId, AccountId), Balance>,
+    pub donation_ledger: Vector<DonationRecord>,
+    pub ledger_by_account: LookupMap<AccountId, Vector<DonationIndex>>,
+    pub ledger_by_cat_id: LookupMap<CategoryId, Vector<DonationIndex>>,
+    pub ledger_by_token_id: LookupMap<TokenId, Vector<DonationIndex>>,
+    pub anonymous_donor_by_token_id: LookupMap<TokenId, AccountId>,
+    pub anonymous_tokens_per_donor: LookupMap<AccountId, HashMap<CategoryId, TokenId>>,
+    pub role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+    pub archived_categories: UnorderedSet<CategoryId>,
+    pub category_id_by_slug: LookupMap<Category, CategoryId>,
+    pub pause_state: PauseState,
+}
+
+
+/// Convert a stringified NEAR amount (e.g. "1.23456") into yoctoNEAR
+/// without going through f64. 
+fn near_str_to_yoctonear(amount: &str) -> Balance {
+  let (whole, fraction) = match amount.split_once('.') {
+    Some((whole, fraction)) => (whole, fraction),
+    None => (amount, ""),
+  };
+
+  // pad (or cut) fraction to exactly 24 decimals. 
+  let mut fraction = fraction.to_string();
+  fraction.truncate(YOCTO_DECIMALS);
+  let fraction = format!("{:0<width$}", fraction, width = YOCTO_DECIMALS);
+
+  let whole: Balance = expect_lightweight(
+    whole.parse().ok(),
+    "Cannot parse old donate amount."
+  );
+  let fraction: Balance = expect_lightweight(
+    fraction.parse().ok(),
+    "Cannot parse old donate amount."
+  );
+
+  whole * ONE_NEAR + fraction
+}
+
+
+/// Find the category a token was minted for from its prefix. 
+/// Token IDs are `category + suffix`, so the longest matching category wins. 
+fn category_by_prefix(categories: &[Category], token_id: &TokenId) -> Option<CategoryId> {
+  let mut found: Option<(CategoryId, usize)> = None;
+
+  for (id, prefix) in categories.iter().enumerate() {
+    let longer = match found {
+      Some((_, len)) => prefix.len() > len,
+      None => true,
+    };
+
+    if token_id.starts_with(prefix.as_str()) && longer {
+      found = Some((id as CategoryId, prefix.len()));
+    }
+  }
+
+  found.map(|(id, _)| id)
+}
+
+
+pub(crate) fn write_state_version() {
+  env::storage_write(
+    &StorageKey::StateVersion.try_to_vec().unwrap(),
+    &CURRENT_STATE_VERSION.try_to_vec().unwrap(),
+  );
+}
+
+fn read_state_version() -> StateVersion {
+  env::storage_read(&StorageKey::StateVersion.try_to_vec().unwrap())
+      .map_or(StateVersion::V1, |bytes| {
+        expect_lightweight(
+          StateVersion::try_from_slice(&bytes).ok(),
+          "Cannot read state version."
+        )
+      })
+}
+
+
+/// Stringified donate amounts become yoctoNEAR, and every existing 
+/// category gets `beneficiary_id` as beneficiary. Goes through all 
+/// tokens at once, so only for small collections. 
+fn migrate_v1(old: OldContract, beneficiary_id: AccountId) -> V2Contract {
+  let mut this = V2Contract {
+    owner_id: old.owner_id,
+    tokens_per_owner: old.tokens_per_owner,
+    tokens_by_id: LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()),
+    token_metadata_by_id: old.token_metadata_by_id,
+    metadata: old.metadata,
+    categories: old.categories,
+    tokens_per_owner_ordered: old.tokens_per_owner_ordered,
+    token_metadata_by_cat_id: old.token_metadata_by_cat_id,
+
+    beneficiary_by_cat_id: LookupMap::new(
+      StorageKey::BeneficiaryByCatId.try_to_vec().unwrap()
+    ),
+    category_by_token_id: LookupMap::new(
+      StorageKey::CategoryByTokenId.try_to_vec().unwrap()
+    ),
+    pending_donations: LookupMap::new(
+      StorageKey::PendingDonations.try_to_vec().unwrap()
+    ),
+    accepted_fts: UnorderedSet::new(
+      StorageKey::AcceptedFts.try_to_vec().unwrap()
+    ),
+    ft_donations_by_token_id: LookupMap::new(
+      StorageKey::FtDonationsByTokenId.try_to_vec().unwrap()
+    ),
+    matching_pools: Vector::new(
+      StorageKey::MatchingPools.try_to_vec().unwrap()
+    ),
+    matching_pools_by_cat_id: LookupMap::new(
+      StorageKey::MatchingPoolsByCatId.try_to_vec().unwrap()
+    ),
+    matched_by_token_id: LookupMap::new(
+      StorageKey::MatchedByTokenId.try_to_vec().unwrap()
+    ),
+    qf_rounds: Vector::new(
+      StorageKey::QfRounds.try_to_vec().unwrap()
+    ),
+    qf_rounds_by_cat_id: LookupMap::new(
+      StorageKey::QfRoundsByCatId.try_to_vec().unwrap()
+    ),
+    qf_tallies: LookupMap::new(
+      StorageKey::QfTallies.try_to_vec().unwrap()
+    ),
+    qf_contributions: LookupMap::new(
+      StorageKey::QfContributions.try_to_vec().unwrap()
+    ),
+    pledges: UnorderedMap::new(
+      StorageKey::Pledges.try_to_vec().unwrap()
+    ),
+    pledge_cursor: 0,
+    campaigns: Vector::new(
+      StorageKey::Campaigns.try_to_vec().unwrap()
+    ),
+    campaigns_by_cat_id: LookupMap::new(
+      StorageKey::CampaignsByCatId.try_to_vec().unwrap()
+    ),
+    campaign_donations: LookupMap::new(
+      StorageKey::CampaignDonations.try_to_vec().unwrap()
+    ),
+    donation_ledger: Vector::new(
+      StorageKey::DonationLedger.try_to_vec().unwrap()
+    ),
+    ledger_by_account: LookupMap::new(
+      StorageKey::LedgerByAccount.try_to_vec().unwrap()
+    ),
+    ledger_by_cat_id: LookupMap::new(
+      StorageKey::LedgerByCatId.try_to_vec().unwrap()
+    ),
+    ledger_by_token_id: LookupMap::new(
+      StorageKey::LedgerByTokenId.try_to_vec().unwrap()
+    ),
+    anonymous_donor_by_token_id: LookupMap::new(
+      StorageKey::AnonymousDonorByTokenId.try_to_vec().unwrap()
+    ),
+    anonymous_tokens_per_donor: LookupMap::new(
+      StorageKey::AnonymousTokensPerDonor.try_to_vec().unwrap()
+    ),
+    role_members: LookupMap::new(
+      StorageKey::RoleMembers.try_to_vec().unwrap()
+    ),
+    archived_categories: UnorderedSet::new(
+      StorageKey::ArchivedCategories.try_to_vec().unwrap()
+    ),
+    category_id_by_slug: LookupMap::new(
+      StorageKey::CategoryIdBySlug.try_to_vec().unwrap()
+    ),
+    pause_state: PauseState::default(),
+  };
+
+  for category_id in 0..this.categories.len() {
+    this.beneficiary_by_cat_id.insert(&(category_id as CategoryId), &beneficiary_id);
+  }
+
+  let categories = this.categories.to_vec();
+
+  // Index slugs; if one was generated twice, the first one keeps it. 
+  for (category_id, category) in categories.iter().enumerate() {
+    if this.category_id_by_slug.get(category).is_none() {
+      this.category_id_by_slug.insert(category, &(category_id as CategoryId));
+    }
+  }
+
+  for token_id in this.token_metadata_by_id.keys_as_vector().iter() {
+    let old_token = expect_lightweight(
+      old.tokens_by_id.get(&token_id),
+      "Cannot find this token in library"
+    );
+
+    let token = Token {
+      owner_id: old_token.owner_id,
+      donate_amount: near_str_to_yoctonear(&old_token.donate_amount),
+      approved_account_ids: old_token.approved_account_ids,
+      next_approval_id: old_token.next_approval_id,
+      royalty: old_token.royalty,
+    };
+
+    this.tokens_by_id.insert(&token_id, &token);
+
+    if let Some(category_id) = category_by_prefix(&categories, &token_id) {
+      this.category_by_token_id.insert(&token_id, &category_id);
+    }
+  }
+
+  this
+}
+
+
+/// New collections start empty, and campaigns are rewritten with 
+/// `closed` and `refunding`. 
+fn migrate_v2(old: V2Contract) -> Contract {
+  let mut campaigns: Vector<Campaign> = Vector::new(
+    StorageKey::Campaigns.try_to_vec().unwrap()
+  );
+
+  // Same prefix: each campaign is read before it's overwritten. 
+  for old_campaign in old.campaigns.iter() {
+    let mut campaign = Campaign {
+      category_id: old_campaign.category_id,
+      title: old_campaign.title,
+      description: old_campaign.description,
+      media: old_campaign.media,
+      target: old_campaign.target,
+      starts_at: old_campaign.starts_at,
+      ends_at: old_campaign.ends_at,
+      after_deadline: old_campaign.after_deadline,
+      mode: old_campaign.mode,
+      raised: old_campaign.raised,
+      donors: old_campaign.donors,
+      escrow_released: old_campaign.escrow_released,
+      refunding: false,
+      closed: false,
+    };
+
+    // Refunds may have been claimed already, without being taken off 
+    // `raised`: never release those. 
+    campaign.refunding = campaign.is_escrow() 
+      && campaign.has_ended() 
+      && !campaign.escrow_released 
+      && campaign.raised < campaign.target;
+
+    campaigns.push(&campaign);
+  }
+
+  Contract {
+    owner_id: old.owner_id,
+    tokens_per_owner: old.tokens_per_owner,
+    tokens_by_id: old.tokens_by_id,
+    token_metadata_by_id: old.token_metadata_by_id,
+    metadata: old.metadata,
+    categories: old.categories,
+    tokens_per_owner_ordered: old.tokens_per_owner_ordered,
+    token_metadata_by_cat_id: old.token_metadata_by_cat_id,
+    beneficiary_by_cat_id: old.beneficiary_by_cat_id,
+    category_by_token_id: old.category_by_token_id,
+    pending_donations: old.pending_donations,
+    accepted_fts: old.accepted_fts,
+    ft_donations_by_token_id: old.ft_donations_by_token_id,
+    matching_pools: old.matching_pools,
+    matching_pools_by_cat_id: old.matching_pools_by_cat_id,
+    matched_by_token_id: old.matched_by_token_id,
+    qf_rounds: old.qf_rounds,
+    qf_rounds_by_cat_id: old.qf_rounds_by_cat_id,
+    qf_tallies: old.qf_tallies,
+    qf_contributions: old.qf_contributions,
+    pledges: old.pledges,
+    pledge_cursor: old.pledge_cursor,
+    campaigns,
+    campaigns_by_cat_id: old.campaigns_by_cat_id,
+    campaign_donations: old.campaign_donations,
+    donation_ledger: old.donation_ledger,
+    ledger_by_account: old.ledger_by_account,
+    ledger_by_cat_id: old.ledger_by_cat_id,
+    ledger_by_token_id: old.ledger_by_token_id,
+    anonymous_donor_by_token_id: old.anonymous_donor_by_token_id,
+    anonymous_tokens_per_donor: old.anonymous_tokens_per_donor,
+    role_members: old.role_members,
+    archived_categories: old.archived_categories,
+    category_id_by_slug: old.category_id_by_slug,
+    pause_state: old.pause_state,
+
+    pending_owner_id: None,
+    soulbound: false,
+    soulbound_categories: UnorderedSet::new(
+      StorageKey::SoulboundCategories.try_to_vec().unwrap()
+    ),
+    transfer_credit_policy: TransferCreditPolicy::StaysWithDonor,
+    tiers_by_cat_id: LookupMap::new(
+      StorageKey::TiersByCatId.try_to_vec().unwrap()
+    ),
+    storage_balances: LookupMap::new(
+      StorageKey::StorageBalances.try_to_vec().unwrap()
+    ),
+    escrowed_donations: LookupMap::new(
+      StorageKey::EscrowedDonations.try_to_vec().unwrap()
+    ),
+    refunded_donations: LookupSet::new(
+      StorageKey::RefundedDonations.try_to_vec().unwrap()
+    ),
+    revealed_donations: LookupSet::new(
+      StorageKey::RevealedDonations.try_to_vec().unwrap()
+    ),
+    anonymous_pending_donations: LookupMap::new(
+      StorageKey::AnonymousPendingDonations.try_to_vec().unwrap()
+    ),
+    anonymous_campaign_donations: LookupMap::new(
+      StorageKey::AnonymousCampaignDonations.try_to_vec().unwrap()
+    ),
+    storage_payer_by_token_id: LookupMap::new(
+      StorageKey::StoragePayerByTokenId.try_to_vec().unwrap()
+    ),
+    burned_token_ids: LookupSet::new(
+      StorageKey::BurnedTokenIds.try_to_vec().unwrap()
+    ),
+    burned_donations: LookupMap::new(
+      StorageKey::BurnedDonations.try_to_vec().unwrap()
+    ),
+  }
+}
+
+
+impl VersionedContract {
+    fn read() -> Self {
+      let state_read_error = "Cannot read old contract state.";
+
+      match read_state_version() {
+        StateVersion::V1 => Self::V1(expect_lightweight(env::state_read(), state_read_error)),
+        StateVersion::V2 => Self::V2(expect_lightweight(env::state_read(), state_read_error)),
+        StateVersion::V3 => Self::V3(expect_lightweight(env::state_read(), state_read_error)),
+      }
+    }
+
+    fn owner_id(&self) -> &AccountId {
+      match self {
+        Self::V1(old) => &old.owner_id,
+        Self::V2(old) => &old.owner_id,
+        Self::V3(old) => &old.owner_id,
+      }
+    }
+
+    /// Migrate forward, one version at a time. 
+    fn into_current(self, beneficiary_id: Option<AccountId>) -> Contract {
+      match self {
+        Self::V1(old) => migrate_v2(migrate_v1(old, expect_lightweight(
+          beneficiary_id,
+          "Migrating from V1 needs a beneficiary_id for existing categories."
+        ))),
+        Self::V2(old) => migrate_v2(old),
+        Self::V3(this) => this,
+      }
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Read the state in whatever layout it's stored and write it back 
+    /// in the current one. `beneficiary_id` is only needed coming from V1. 
+    /// Called by the owner after deploying, or by `upgrade`. 
+    #[init(ignore_state)]
+    pub fn migrate(beneficiary_id: Option<AccountId>) -> Self {
+      let old = VersionedContract::read();
+
+      require!(
+        env::predecessor_account_id() == env::current_account_id()
+        || &env::predecessor_account_id() == old.owner_id(),
+        "Only the owner can migrate."
+      );
+
+      let this = old.into_current(beneficiary_id);
+      write_state_version();
+
+      this
+    }
+
+
+    /// Deploy the code passed as raw input (not JSON) onto this contract, 
+    /// then `migrate` to it, keeping every token. Owner only. 
+    /// 
+    /// near call $CONTRACT upgrade --base64 "$(base64 -w0 res/output_s.wasm)" \
+    ///   --accountId $OWNER --gas 300000000000000
+    pub fn upgrade(&self) -> Promise {
+      require!(
+        env::predecessor_account_id() == self.owner_id,
+        "Only the owner can upgrade."
+      );
+
+      let code = expect_lightweight(env::input(), "Pass the new contract code as input.");
+
+      require!(
+        env::prepaid_gas() > env::used_gas() + GAS_FOR_UPGRADE,
+        format!("Attach more than {} gas to upgrade.", GAS_FOR_UPGRADE.0)
+      );
+
+      Promise::new(env::current_account_id())
+          .deploy_contract(code)
+          .function_call(
+            "migrate".to_string(),
+            b"{}".to_vec(),
+            0,
+            env::prepaid_gas() - env::used_gas() - GAS_FOR_UPGRADE,
+          )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{
+      beneficiary_id, donor_id, owner_id, sample_metadata, set_caller, setup_contract,
+    };
+
+    /// Write a V1 state with categories "animal" and "animal-rescue", 
+    /// and a token with each of `amounts`, as stored before versioning. 
+    fn write_old_contract(amounts: &[(&str, &str)]) {
+      let mut old = OldContract {
+        owner_id: owner_id(),
+        tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner.try_to_vec().unwrap()),
+        tokens_by_id: LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()),
+        token_metadata_by_id: UnorderedMap::new(
+          StorageKey::TokenMetadataById.try_to_vec().unwrap(),
+        ),
+        metadata: LazyOption::new(
+          StorageKey::NFTContractMetadata.try_to_vec().unwrap(),
+          None,
+        ),
+        categories: Vector::new(StorageKey::Categories.try_to_vec().unwrap()),
+        tokens_per_owner_ordered: LookupMap::new(
+          StorageKey::TokensOrdered.try_to_vec().unwrap()
+        ),
+        token_metadata_by_cat_id: UnorderedMap::new(
+          StorageKey::MetadataCatId.try_to_vec().unwrap()
+        ),
+      };
+
+      for (category_id, category) in ["animal", "animal-rescue"].iter().enumerate() {
+        old.categories.push(&category.to_string());
+        old.token_metadata_by_cat_id.insert(&(category_id as CategoryId), &sample_metadata(category));
+      }
+
+      for (token_id, donate_amount) in amounts {
+        old.tokens_by_id.insert(&token_id.to_string(), &OldToken {
+          owner_id: donor_id(),
+          donate_amount: donate_amount.to_string(),
+          approved_account_ids: HashMap::new(),
+          next_approval_id: 0,
+          royalty: HashMap::new(),
+        });
+        old.token_metadata_by_id.insert(&token_id.to_string(), &sample_metadata(token_id));
+      }
+
+      env::state_write(&old);
+    }
+
+
+    #[test]
+    fn v1_donate_amounts_become_yoctonear() {
+      set_caller(&owner_id(), 0);
+      write_old_contract(&[
+        ("animal-whole", "2"),
+        ("animal-half", "1.5"),
+        ("animal-yocto", "0.000000000000000000000001"),
+        ("animal-long", "0.1234567890123456789012345"),
+      ]);
+
+      let contract = Contract::migrate(Some(beneficiary_id()));
+      let donate_amount = |token_id: &str| {
+        contract.tokens_by_id.get(&token_id.to_string()).unwrap().donate_amount
+      };
+
+      assert_eq!(donate_amount("animal-whole"), 2 * ONE_NEAR);
+      assert_eq!(donate_amount("animal-half"), 3 * ONE_NEAR / 2);
+      assert_eq!(donate_amount("animal-yocto"), 1);
+      // beyond 24 decimals is cut off. 
+      assert_eq!(donate_amount("animal-long"), 123_456_789_012_345_678_901_234);
+    }
+
+
+    #[test]
+    fn v1_categories_get_beneficiaries_and_slugs() {
+      set_caller(&owner_id(), 0);
+      write_old_contract(&[("animal-donor", "1"), ("animal-rescue-donor", "1")]);
+
+      let contract = Contract::migrate(Some(beneficiary_id()));
+
+      for (category_id, category) in ["animal", "animal-rescue"].iter().enumerate() {
+        let category_id = category_id as CategoryId;
+
+        assert_eq!(contract.get_category_id(category.to_string()), Some(category_id));
+        assert_eq!(contract.get_beneficiary(CategoryKey::Id(category_id)), Some(beneficiary_id()));
+      }
+
+      // the longest matching prefix wins. 
+      assert_eq!(contract.category_by_token_id.get(&"animal-donor".to_string()), Some(0));
+      assert_eq!(contract.category_by_token_id.get(&"animal-rescue-donor".to_string()), Some(1));
+      assert!(read_state_version() == CURRENT_STATE_VERSION);
+    }
+
+
+    #[test]
+    #[should_panic(expected = "Migrating from V1 needs a beneficiary_id for existing categories.")]
+    fn v1_needs_beneficiary() {
+      set_caller(&owner_id(), 0);
+      write_old_contract(&[]);
+
+      Contract::migrate(None);
+    }
+
+
+    /// Write the state of `setup_contract` in the V2 layout, with an 
+    /// all-or-nothing campaign on "animal" for each of `ends_at`, 
+    /// each having raised 1N out of 2N. 
+    fn write_v2_contract(ends_at: &[u64]) {
+      let this = setup_contract();
+
+      let mut old = V2Contract {
+        owner_id: this.owner_id,
+        tokens_per_owner: this.tokens_per_owner,
+        tokens_by_id: this.tokens_by_id,
+        token_metadata_by_id: this.token_metadata_by_id,
+        metadata: this.metadata,
+        categories: this.categories,
+        tokens_per_owner_ordered: this.tokens_per_owner_ordered,
+        token_metadata_by_cat_id: this.token_metadata_by_cat_id,
+        beneficiary_by_cat_id: this.beneficiary_by_cat_id,
+        category_by_token_id: this.category_by_token_id,
+        pending_donations: this.pending_donations,
+        accepted_fts: this.accepted_fts,
+        ft_donations_by_token_id: this.ft_donations_by_token_id,
+        matching_pools: this.matching_pools,
+        matching_pools_by_cat_id: this.matching_pools_by_cat_id,
+        matched_by_token_id: this.matched_by_token_id,
+        qf_rounds: this.qf_rounds,
+        qf_rounds_by_cat_id: this.qf_rounds_by_cat_id,
+        qf_tallies: this.qf_tallies,
+        qf_contributions: this.qf_contributions,
+        pledges: this.pledges,
+        pledge_cursor: this.pledge_cursor,
+        campaigns: Vector::new(StorageKey::Campaigns.try_to_vec().unwrap()),
+        campaigns_by_cat_id: this.campaigns_by_cat_id,
+        campaign_donations: this.campaign_donations,
+        donation_ledger: this.donation_ledger,
+        ledger_by_account: this.ledger_by_account,
+        ledger_by_cat_id: this.ledger_by_cat_id,
+        ledger_by_token_id: this.ledger_by_token_id,
+        anonymous_donor_by_token_id: this.anonymous_donor_by_token_id,
+        anonymous_tokens_per_donor: this.anonymous_tokens_per_donor,
+        role_members: this.role_members,
+        archived_categories: this.archived_categories,
+        category_id_by_slug: this.category_id_by_slug,
+        pause_state: this.pause_state,
+      };
+
+      for (campaign_id, ends_at) in ends_at.iter().enumerate() {
+        old.campaigns.push(&V2Campaign {
+          category_id: 0,
+          title: format!("campaign {}", campaign_id),
+          description: None,
+          media: None,
+          target: 2 * ONE_NEAR,
+          starts_at: 0,
+          ends_at: *ends_at,
+          after_deadline: AfterDeadline::Reject,
+          mode: CampaignMode::AllOrNothing,
+          raised: ONE_NEAR,
+          donors: 1,
+          escrow_released: false,
+        });
+      }
+
+      env::state_write(&old);
+      env::storage_write(
+        &StorageKey::StateVersion.try_to_vec().unwrap(),
+        &StateVersion::V2.try_to_vec().unwrap(),
+      );
+    }
+
+
+    #[test]
+    fn v2_campaigns_are_rewritten() {
+      set_caller(&owner_id(), 0);
+      write_v2_contract(&[0, u64::MAX]);
+
+      let migrated = Contract::migrate(None);
+
+      assert_eq!(migrated.get_owner(), owner_id());
+      assert_eq!(migrated.get_beneficiary(CategoryKey::Id(0)), Some(beneficiary_id()));
+      assert!(migrated.get_transfer_credit_policy() == TransferCreditPolicy::StaysWithDonor);
+      assert!(read_state_version() == CURRENT_STATE_VERSION);
+
+      // ended below target: refunds may have been paid, never release. 
+      let ended = migrated.get_campaign(0).unwrap();
+      assert_eq!(ended.title, "campaign 0");
+      assert_eq!(ended.raised, U128(ONE_NEAR));
+      assert!(ended.refunding && !ended.closed);
+
+      let running = migrated.get_campaign(1).unwrap();
+      assert_eq!(running.title, "campaign 1");
+      assert!(!running.refunding && !running.closed);
+    }
+
+
+    #[test]
+    fn v3_round_trips() {
+      let mut contract = setup_contract();
+
+      set_caller(&owner_id(), 0);
+      contract.set_transfer_credit_policy(TransferCreditPolicy::TravelsWithToken);
+      env::state_write(&contract);
+      write_state_version();
+
+      let migrated = Contract::migrate(None);
+
+      assert_eq!(migrated.get_owner(), owner_id());
+      assert_eq!(migrated.get_categories(), vec!["animal".to_string()]);
+      assert_eq!(migrated.get_beneficiary(CategoryKey::Id(0)), Some(beneficiary_id()));
+      assert!(migrated.get_transfer_credit_policy() == TransferCreditPolicy::TravelsWithToken);
+    }
+
+
+    #[test]
+    #[should_panic(expected = "Only the owner can migrate.")]
+    fn non_owner_cannot_migrate() {
+      set_caller(&owner_id(), 0);
+      write_old_contract(&[]);
+
+      set_caller(&donor_id(), 0);
+      Contract::migrate(Some(beneficiary_id()));
+    }
+}
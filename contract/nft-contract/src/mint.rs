@@ -2,26 +2,88 @@ use crate::*;
 use near_sdk::require;
 
 
-const GAS_PER_DONATE: Gas = Gas(20_000_000_000_000);
+const GAS_PER_DONATE: Gas = Gas(25_000_000_000_000);
 const GAS_FOR_MINTING: Gas = Gas(10_000_000_000_000);  // 10 TGas.
+const GAS_FOR_RESOLVE_NFT_MINT: Gas = Gas(5_000_000_000_000);
+const GAS_FOR_RESOLVE_MINTING: Gas = Gas(20_000_000_000_000);
+const GAS_FOR_RESOLVE_BATCH: Gas = Gas(20_000_000_000_000);
+
+/// Attached on top of each mint for storage, mostly refunded. 
+pub(crate) const STORAGE_PER_MINT: Balance = 100_000_000_000_000_000_000_000;  // 0.1N
+/// How much more than `STORAGE_PER_MINT` one may attach to a mint. 
+const STORAGE_PER_MINT_TOLERANCE: Balance = 1_000_000_000_000_000_000_000;  // 0.001N
+
+const MAX_GIFT_MESSAGE_LEN: usize = 280;
+
+
+/// What a single category of `minting_interface` is doing, 
+/// passed on to `resolve_minting_interface`. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CategoryDonation {
+    pub category_id: CategoryId,
+    pub token_id: TokenId,
+    pub deposit: U128,  // total attached for this category. 
+    pub is_mint: bool,
+    // campaign holding the donation in escrow, `batch_donate` only. 
+    pub escrow_campaign_id: Option<CampaignId>,
+}
+
+
+/// Donations of `batch_donate` going to the same beneficiary, 
+/// paid out in a single transfer. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BeneficiaryTransfer {
+    pub beneficiary_id: AccountId,
+    pub donations: Vec<CategoryDonation>,
+}
 
 
 #[ext_contract(ext_self)]
 trait ExtSelf {
+    #[allow(clippy::too_many_arguments)]
     fn nft_mint(
       &mut self,
       token_id:TokenId,
-      donate_amount_str: String, 
+      category_id: CategoryId,
+      donate_amount: U128, 
       metadata: TokenMetadata,
       receiver_id: AccountId,
       perpetual_royalties: Option<HashMap<AccountId, u16>>,
-    );
+      anonymous: Option<bool>,
+      escrow_campaign_id: Option<CampaignId>,
+    ) -> bool;
+
+    fn resolve_nft_mint(
+      &mut self,
+      token_id: TokenId,
+      donate_amount: U128,
+      anonymous: bool,
+      escrow_campaign_id: Option<CampaignId>,
+    ) -> bool;
 
     fn on_donate_update(
       &mut self,
       token_id: TokenId,
-      old_donate_amount: f64,
-    );
+      donate_amount: U128,
+      anonymous: bool,
+      escrow_campaign_id: Option<CampaignId>,
+    ) -> bool;
+
+    fn resolve_minting_interface(
+      &mut self,
+      account_id: AccountId,
+      owner_id: AccountId,
+      donations: Vec<CategoryDonation>,
+      anonymous: bool,
+    ) -> HashMap<CategoryId, bool>;
+
+    fn resolve_batch_donate(
+      &mut self,
+      account_id: AccountId,
+      transfers: Vec<BeneficiaryTransfer>,
+    ) -> HashMap<CategoryId, bool>;
 
     /// These two functions can be called by non-contract. 
     /// We use callback because another function needs to call
@@ -29,14 +91,20 @@ trait ExtSelf {
     fn donate_and_update(
       &mut self,
       token_id: TokenId,
-      donate_amount: f64,
+      donate_amount: U128,
+      anonymous: Option<bool>,
     );
 
+    #[allow(clippy::too_many_arguments)]
     fn donate_and_mint(
       &mut self,
       token_id: TokenId,
+      category_id: CategoryKey,
       metadata: TokenMetadata,
-      donate_amount: f64,
+      donate_amount: U128,
+      receiver_id: Option<AccountId>,
+      gift_message: Option<String>,
+      anonymous: Option<bool>,
     );
 }
 
@@ -46,46 +114,90 @@ impl Contract {
     /// exist or not. 
     /// 
     /// To ensure no subtraction overflow, please attach more than required. 
+    /// 
+    /// Pass `receiver_id` to donate in honour of someone else: the tokens 
+    /// are theirs, with `gift_message` in their metadata `extra`, while 
+    /// the donation stays credited to you. 
+    /// 
+    /// With `anonymous`, you don't show up in public views or events: 
+    /// the tokens are held by the contract until you claim them with 
+    /// `claim_anonymous_token` (or go to `receiver_id`, without your name; 
+    /// `reveal_anonymous_donations` credits those to you later). 
+    /// 
+    /// Returns whether each category's donation went through. Failed ones 
+    /// are removed from `tokens_per_owner_ordered` and refunded. 
     #[payable]
     pub fn minting_interface(
       &mut self,
       suffix_token_id: String,
-      hash_of_amounts: HashMap<CategoryId, f64>,
+      hash_of_amounts: HashMap<CategoryKey, U128>,
       issued_at: Option<u64>,
-    ) {
+      receiver_id: Option<AccountId>,
+      gift_message: Option<String>,
+      anonymous: Option<bool>,
+    ) -> Promise {
+      self.internal_assert_not_paused(PauseScope::Donations);
       require!(
         env::predecessor_account_id() == env::signer_account_id(),
         "This method can only be called by signer."
       );
 
-      let mut token_id_list: HashMap<CategoryId, TokenId> = match 
-          self.tokens_per_owner_ordered.get(&env::predecessor_account_id()) 
-      {
-        Some(value) => value,
-        None => HashMap::new()
-      };
+      require!(
+        !hash_of_amounts.is_empty(),
+        "Please donate to at least one category."
+      );
+
+      let hash_of_amounts = self.internal_category_amounts(hash_of_amounts);
+      let anonymous = anonymous.unwrap_or(false);
+      let owner_id = receiver_id.clone().unwrap_or_else(|| {
+        if anonymous { env::current_account_id() } else { env::predecessor_account_id() }
+      });
+
+      // Checked again by `donate_and_mint`, but fail before any transfer. 
+      assert_gift_message(&owner_id, &gift_message);
+
+      let mut token_id_list: HashMap<CategoryId, TokenId> = self.internal_ordered_tokens(
+        &env::predecessor_account_id(), 
+        &owner_id
+      );
 
       let mut total_use: u128 = 0;
+
+      // Keep track of what each promise is for, in the same order as 
+      // the promises, so the resolver can read its results. 
+      let mut donations: Vec<CategoryDonation> = Vec::new();
+      let mut promises: Option<Promise> = None;
       
 
       for (id, amount) in hash_of_amounts {
+        self.internal_assert_accepts_donations(id);
+
         // Check if token already exist. 
-        if let Some(token_id) = token_id_list.get(&id) {
+        let promise = if let Some(token_id) = token_id_list.get(&id) {
           // Due to some errors, we overwrite when cannot find. 
           if let Some(_token) = self.tokens_by_id.get(&token_id) {
-            let attached = near_to_yoctonear(amount);
+            let attached = amount.0;
             
             total_use += attached;
+            donations.push(CategoryDonation {
+              category_id: id,
+              token_id: token_id.clone(),
+              deposit: U128(attached),
+              is_mint: false,
+              escrow_campaign_id: None,
+            });
+
             ext_self::donate_and_update(
               token_id.clone(),
               amount,
+              Some(anonymous),
   
               env::current_account_id(),
               attached,
               GAS_PER_DONATE,
-            );
+            )
           } else {
-          let attached = near_to_yoctonear(amount + 0.1);
+          let attached = amount.0 + STORAGE_PER_MINT;
           total_use += attached;
             // Get metadata from lookupmap. 
           let mut metadata = expect_lightweight(
@@ -95,19 +207,31 @@ impl Contract {
 
           metadata.issued_at = issued_at;
 
+            donations.push(CategoryDonation {
+              category_id: id,
+              token_id: token_id.clone(),
+              deposit: U128(attached),
+              is_mint: true,
+              escrow_campaign_id: None,
+            });
+
             ext_self::donate_and_mint(
               token_id.clone(),
+              CategoryKey::Id(id),
               metadata,
               amount,
+              receiver_id.clone(),
+              gift_message.clone(),
+              Some(anonymous),
   
               env::current_account_id(),
               attached,
               GAS_PER_DONATE
-            );
+            )
           }
           
         } else {
-          let attached = near_to_yoctonear(amount + 0.1);
+          let attached = amount.0 + STORAGE_PER_MINT;
           total_use += attached;
 
           // Get prefix
@@ -117,7 +241,7 @@ impl Contract {
           );
 
           // Create token_id based on suffix. 
-          let token_id: TokenId = prefix + suffix_token_id.as_str();
+          let token_id: TokenId = self.internal_unique_token_id(prefix + suffix_token_id.as_str());
 
           // Add to list
           token_id_list.insert(id.clone(), token_id.clone());
@@ -130,17 +254,34 @@ impl Contract {
 
           metadata.issued_at = issued_at;
 
+          donations.push(CategoryDonation {
+            category_id: id,
+            token_id: token_id.clone(),
+            deposit: U128(attached),
+            is_mint: true,
+            escrow_campaign_id: None,
+          });
+
           // Cross contract call. 
           ext_self::donate_and_mint(
             token_id,
+            CategoryKey::Id(id),
             metadata,
             amount,
+            receiver_id.clone(),
+            gift_message.clone(),
+            Some(anonymous),
 
             env::current_account_id(),
             attached,
             GAS_PER_DONATE
-          );
-        }
+          )
+        };
+
+        promises = Some(match promises {
+          Some(promises) => promises.and(promise),
+          None => promise,
+        });
       }
 
       let refund_amount = env::attached_deposit() - total_use;
@@ -150,52 +291,283 @@ impl Contract {
             .transfer(refund_amount);
       }
 
-      // Insert token id list back.
-      // Weakness: if something fails, this will not get deleted... 
-      // like during minting. 
-      self.tokens_per_owner_ordered.insert(
+      // Insert token id list back. If something fails, 
+      // `resolve_minting_interface` takes the failed ones out again. 
+      self.internal_set_ordered_tokens(
         &env::predecessor_account_id(), 
+        &owner_id, 
         &token_id_list
       );
+
+      promises.unwrap().then(
+        ext_self::resolve_minting_interface(
+          env::predecessor_account_id(),
+          owner_id,
+          donations,
+          anonymous,
+
+          env::current_account_id(),
+          0,
+          GAS_FOR_RESOLVE_MINTING
+        )
+      )
+    }
+
+
+    /// Resolve `minting_interface`: read the result of each category's 
+    /// donation and revert `tokens_per_owner_ordered` of `owner_id` for 
+    /// failed mints. Failed transfers are refunded by their callbacks 
+    /// already; only calls that failed before transferring are refunded 
+    /// here, to the donor `account_id`. 
+    #[private]
+    pub fn resolve_minting_interface(
+      &mut self,
+      account_id: AccountId,
+      owner_id: AccountId,
+      donations: Vec<CategoryDonation>,
+      anonymous: bool,
+    ) -> HashMap<CategoryId, bool> {
+      let mut report: HashMap<CategoryId, bool> = HashMap::new();
+      let mut token_id_list = self.internal_ordered_tokens(&account_id, &owner_id);
+
+      for (index, donation) in donations.into_iter().enumerate() {
+        let (success, refunded) = match env::promise_result(index as u64) {
+          // resolve_nft_mint and on_donate_update return whether the transfer 
+          // went through, and refund the donor themselves if it didn't. 
+          PromiseResult::Successful(value) => (
+            near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false),
+            true
+          ),
+          // Failed before transferring, so the deposit came back to us. 
+          _ => (false, false),
+        };
+
+        if !success {
+          // Token never got minted, don't point to it (unless another 
+          // donation minted it in the meantime). 
+          if donation.is_mint 
+              && token_id_list.get(&donation.category_id) == Some(&donation.token_id) 
+              && self.tokens_by_id.get(&donation.token_id).is_none()
+          {
+            token_id_list.remove(&donation.category_id);
+          }
+
+          if !refunded {
+            self.internal_refund_donation(
+              &account_id,
+              &donation.token_id,
+              donation.deposit.0,
+              anonymous,
+              "Donation failed before transfer.",
+            );
+          }
+        }
+
+        report.insert(donation.category_id, success);
+      }
+
+      self.internal_set_ordered_tokens(&account_id, &owner_id, &token_id_list);
+
+      report
+    }
+
+
+    /// Same as `minting_interface`, but all tokens are minted or updated
+    /// right here in a single function call instead of one cross-contract 
+    /// call per category. Only the transfers to beneficiaries are promises, 
+    /// one per beneficiary. 
+    /// 
+    /// Attach the total donation plus storage; the unused part is refunded. 
+    #[payable]
+    pub fn batch_donate(
+      &mut self,
+      suffix_token_id: String,
+      hash_of_amounts: HashMap<CategoryKey, U128>,
+      issued_at: Option<u64>,
+    ) -> Promise {
+      self.internal_assert_not_paused(PauseScope::Donations);
+      require!(
+        env::predecessor_account_id() == env::signer_account_id(),
+        "This method can only be called by signer."
+      );
+
+      let account_id = env::predecessor_account_id();
+      let hash_of_amounts = self.internal_category_amounts(hash_of_amounts);
+      let total_donation: u128 = hash_of_amounts.values().map(|amount| amount.0).sum();
+
+      let (promise, required_cost_to_store_info) = self.internal_batch_donate(
+        &account_id,
+        &suffix_token_id,
+        hash_of_amounts,
+        issued_at,
+      );
+
+      // Storage is paid out of the attached deposit, on top of the donations. 
+      require!(
+        env::attached_deposit() >= total_donation + required_cost_to_store_info,
+        format!(
+          "Must attach {} yoctoNEAR to cover donations and storage",
+          total_donation + required_cost_to_store_info
+        )
+      );
+
+      let refund_amount = env::attached_deposit() - total_donation - required_cost_to_store_info;
+
+      if refund_amount > 1 {
+        Promise::new(account_id).transfer(refund_amount);
+      }
+
+      promise
+    }
+
+
+    /// Resolve `batch_donate`: for every beneficiary transfer that failed, 
+    /// take the donations back off their tokens and refund them to `account_id`. 
+    /// Tokens minted in the batch are kept. 
+    #[private]
+    pub fn resolve_batch_donate(
+      &mut self,
+      account_id: AccountId,
+      transfers: Vec<BeneficiaryTransfer>,
+    ) -> HashMap<CategoryId, bool> {
+      let mut report: HashMap<CategoryId, bool> = HashMap::new();
+
+      for (index, transfer) in transfers.into_iter().enumerate() {
+        let success = matches!(
+          env::promise_result(index as u64), 
+          PromiseResult::Successful(_)
+        );
+
+        for donation in transfer.donations {
+          self.internal_remove_pending_donation(&account_id, donation.deposit.0, false);
+
+          if success {
+            self.internal_record_donation(
+              &account_id,
+              donation.category_id, 
+              &donation.token_id, 
+              donation.deposit.0,
+              false,
+              donation.escrow_campaign_id,
+            );
+          } else {
+            if let Some(token) = self.tokens_by_id.get(&donation.token_id) {
+              let donate_amount = token.donate_amount.saturating_sub(donation.deposit.0);
+              self.internal_set_donate_amount(&donation.token_id, token, donate_amount);
+            }
+
+            self.internal_refund_donation(
+              &account_id,
+              &donation.token_id,
+              donation.deposit.0,
+              false,
+              "Transfer to beneficiary failed.",
+            );
+          }
+
+          report.insert(donation.category_id, success);
+        }
+      }
+
+      report
     }
 
 
     /// Donate money and we'll mint and nft for you. 
-    /// donate_amount is not U128 to reduce gas cost? We infer from f64 instead. 
+    /// donate_amount is in yoctoNEAR. 
     /// Total pay will be donate_amount + 0.1N (which mostly refunded for storage).
+    /// The donation is forwarded to the beneficiary of `category_id`. 
+    /// Resolves to whether it went through (failed ones are refunded). 
+    /// 
+    /// The token goes to `receiver_id` if given (a gift, with the optional 
+    /// `gift_message`), else to the signer. The signer keeps the credit. 
+    /// An `anonymous` token without receiver is held by the contract. 
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn donate_and_mint(
       &mut self,
       token_id: TokenId,
+      category_id: CategoryKey,
       metadata: TokenMetadata,
-      donate_amount: f64,
-    ) {
-      let donate_amount_u128: u128 = near_to_yoctonear(donate_amount);
+      donate_amount: U128,
+      receiver_id: Option<AccountId>,
+      gift_message: Option<String>,
+      anonymous: Option<bool>,
+    ) -> Promise {
+      self.internal_assert_not_paused(PauseScope::Donations);
+      let donate_amount_u128: u128 = donate_amount.0;
       require!(
-        env::attached_deposit() >= (donate_amount_u128 + near_to_yoctonear(0.1)),
+        env::attached_deposit() >= (donate_amount_u128 + STORAGE_PER_MINT),
         "You attached less than you want to donate + 0.1N for storage (mostly refunded)."
       );
   
       require!(
-        env::attached_deposit() <= (donate_amount_u128 + near_to_yoctonear(0.101)),
+        env::attached_deposit() <= (donate_amount_u128 + STORAGE_PER_MINT + STORAGE_PER_MINT_TOLERANCE),
         "You attached too much money than you want to donate. Try again!"
       );
 
-      Promise::new(env::current_account_id())
+      let category_id = self.internal_category_id(&category_id);
+      self.internal_assert_accepts_donations(category_id);
+
+      let mut metadata = metadata;
+      let anonymous = anonymous.unwrap_or(false);
+      let receiver_id = receiver_id.unwrap_or_else(|| {
+        if anonymous { env::current_account_id() } else { env::signer_account_id() }
+      });
+
+      assert_gift_message(&receiver_id, &gift_message);
+
+      if receiver_id != env::signer_account_id() && receiver_id != env::current_account_id() {
+        let donor_id = if anonymous { None } else { Some(env::signer_account_id()) };
+        metadata.extra = Some(gift_extra(donor_id, gift_message));
+      }
+
+      let (beneficiary_id, escrow_campaign_id) = self.internal_route_donation(category_id);
+
+      // `nft_mint` gets STORAGE_PER_MINT and refunds what it doesn't use; 
+      // the tolerance above that goes back right away. 
+      let excess = env::attached_deposit() - donate_amount_u128 - STORAGE_PER_MINT;
+      if excess > 0 {
+        Promise::new(env::signer_account_id()).transfer(excess);
+      }
+
+      // Until nft_mint resolves, we owe this to the signer. 
+      self.internal_add_pending_donation(
+        &env::signer_account_id(), 
+        donate_amount_u128 + STORAGE_PER_MINT,
+        anonymous,
+      );
+
+      Promise::new(beneficiary_id)
           .transfer(donate_amount_u128)
           .then(
             ext_self::nft_mint(
-              token_id,
-              donate_amount.to_string(),
+              token_id.clone(),
+              category_id,
+              donate_amount,
               metadata,
-              env::signer_account_id(),
+              receiver_id,
               None,
+              Some(anonymous),
+              escrow_campaign_id,
 
               env::current_account_id(),
-              near_to_yoctonear(0.1),
+              STORAGE_PER_MINT,
               GAS_FOR_MINTING
             )
-      );
+          )
+          .then(
+            ext_self::resolve_nft_mint(
+              token_id,
+              donate_amount,
+              anonymous,
+              escrow_campaign_id,
+
+              env::current_account_id(),
+              0,
+              GAS_FOR_RESOLVE_NFT_MINT
+            )
+      )
     }
 
 
@@ -207,13 +579,18 @@ impl Contract {
     /// 
     /// Subsequent storage usage, we'll pay for them, taken out from their donations. 
     /// This value is so small it's ignorable. (less than 1 cent most probably).
+    /// 
+    /// Resolves to whether the donation went through. `anonymous` keeps 
+    /// the signer out of the public views of this donation. 
     #[payable]
     pub fn donate_and_update(
       &mut self,
       token_id: TokenId,
-      donate_amount: f64,
-    ) {
-      let donate_amount_u128: u128 = near_to_yoctonear(donate_amount);
+      donate_amount: U128,
+      anonymous: Option<bool>,
+    ) -> Promise {
+      self.internal_assert_not_paused(PauseScope::Donations);
+      let donate_amount_u128: u128 = donate_amount.0;
       require!(
         env::attached_deposit() >= donate_amount_u128,
         "You attached less than you want to donate + 0.1N for storage (mostly refunded)."
@@ -224,48 +601,75 @@ impl Contract {
         "You attached too much money than you want to donate. Try again!"
       );
 
-      let mut token = expect_lightweight(
+      let token = expect_lightweight(
         self.tokens_by_id.get(&token_id),
         "This token_id cannot be found."
       );
 
-      let old_donate_amount: f64 = token.donate_amount.parse().unwrap();
-      let new_donate_amount: u128 = near_to_yoctonear(old_donate_amount) + donate_amount_u128;
+      let category_id = expect_lightweight(
+        self.category_by_token_id.get(&token_id),
+        "Cannot find the category of this token. Please contact support."
+      );
+      self.internal_assert_accepts_donations(category_id);
+      let (beneficiary_id, escrow_campaign_id) = self.internal_route_donation(category_id);
+      let anonymous = anonymous.unwrap_or(false);
 
-      // Function only converts to first 5 decimals.
-      token.donate_amount = yoctonear_to_near(new_donate_amount).to_string(); 
+      let donate_amount_after = token.donate_amount + donate_amount_u128;
+      self.internal_set_donate_amount(&token_id, token, donate_amount_after);
 
-      self.tokens_by_id.insert(&token_id, &token);
+      // Until on_donate_update resolves, we owe this to the signer. 
+      self.internal_add_pending_donation(&env::signer_account_id(), donate_amount_u128, anonymous);
 
-      Promise::new(env::current_account_id())
+      Promise::new(beneficiary_id)
             .transfer(env::attached_deposit())
             .then(
               ext_self::on_donate_update(
                 token_id,
-                old_donate_amount,
+                donate_amount,
+                anonymous,
+                escrow_campaign_id,
 
                 env::current_account_id(),
                 0,
                 GAS_FOR_MINTING  // just use this is fine.
               )
-      );
+      )
     }
 
 
+    /// Mint to `receiver_id` after the donation transfer. If the transfer 
+    /// failed, the donation and storage deposit are refunded to the signer 
+    /// instead, and this returns false. The signer is credited either way. 
+    /// 
+    /// Once the transfer went through this must not panic: the deposit 
+    /// would come back to us and be refunded although the beneficiary 
+    /// got the donation. Hence `token_id` is only a wish; if it's taken 
+    /// by now (e.g. by a concurrent donation), a free one is used. 
     #[private]
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     pub fn nft_mint(
         &mut self,
         token_id:TokenId,
-        donate_amount_str: String,
+        category_id: CategoryId,
+        donate_amount: U128,
         metadata: TokenMetadata,
         receiver_id: AccountId,
         perpetual_royalties: Option<HashMap<AccountId, u16>>,
-    ) {
+        anonymous: Option<bool>,
+        escrow_campaign_id: Option<CampaignId>,
+    ) -> bool {
         if !is_promise_success() {
-          // Ok, I don't know what would happen, so let's just "contact support."
-          // assuming there's a support. 
-          env::panic_str("Transfer donation failed. Please contact support.");
+          // Donation came back to us, return it with the storage deposit. 
+          self.internal_refund_donation(
+            &env::signer_account_id(),
+            &token_id,
+            donate_amount.0 + env::attached_deposit(),
+            anonymous.unwrap_or(false),
+            "Transfer to beneficiary failed.",
+          );
+
+          return false;
         }
 
         // For this specific use case, only the contract can mint. 
@@ -278,6 +682,11 @@ impl Contract {
           )
         );
 
+        // `minting_interface` already put `requested_token_id` in the 
+        // ordered map; we may have to mint under another id. 
+        let requested_token_id = token_id;
+        let token_id = self.internal_unique_token_id(requested_token_id.clone());
+
         // measure the initial storage being used on contract.
         let initial_storage_usage = env::storage_usage();
 
@@ -297,42 +706,47 @@ impl Contract {
           }
         }
 
-
-        // We need contract be approved so it can pull back nft
-        // when user donate the next time, to update it. 
-
-
-        // specify the token struct that contains the owner ID. 
-        let mut token = Token {
-          owner_id: receiver_id,
-          donate_amount: donate_amount_str,
-          approved_account_ids: Default::default(),  // default value is empty map.
-          next_approval_id: 1,  // 0 will be inserted later.
+        self.internal_mint(
+          &token_id,
+          category_id,
+          donate_amount.0,
+          &metadata,
+          &receiver_id,
           royalty,
-        };
+        );
 
-        let _ = token
-              .approved_account_ids
-              .insert(env::current_account_id(), 0)  // approved id of 0 inserted here.
-              .is_none();
+        // paid out of the attached deposit, even for gifts. 
+        self.storage_payer_by_token_id.insert(&token_id, &env::signer_account_id());
 
-        // insert token ID and token struct and make sure token
-        // doesn't exist. 
-        require!(
-          self.tokens_by_id.insert(&token_id, &token).is_none(),
-          "Token already exists."
-        );
+        // Held for an anonymous donor until they claim it. 
+        if receiver_id == env::current_account_id() {
+          self.anonymous_donor_by_token_id.insert(&token_id, &env::signer_account_id());
+        }
 
-        self.token_metadata_by_id.insert(&token_id, &metadata);
+        // Point the ordered map to the id actually minted. A gift (or a 
+        // direct `donate_and_mint`) isn't in it yet; don't replace a token 
+        // the receiver already has though. 
+        let mut token_id_list = self.internal_ordered_tokens(&env::signer_account_id(), &receiver_id);
+        let keep_ordered = match token_id_list.get(&category_id) {
+          Some(ordered_id) if ordered_id == &token_id => true,
+          Some(ordered_id) if ordered_id != &requested_token_id => matches!(
+            self.tokens_by_id.get(ordered_id),
+            Some(token) if token.owner_id == receiver_id
+          ),
+          _ => false,
+        };
 
-        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+        if !keep_ordered {
+          token_id_list.insert(category_id, token_id.clone());
+          self.internal_set_ordered_tokens(&env::signer_account_id(), &receiver_id, &token_id_list);
+        }
 
         // Log the minting as per events standard. 
         let nft_mint_log: EventLog = EventLog {
           standard: NFT_STANDARD_NAME.to_string(),
           version : NFT_METADATA_SPEC.to_string(),
           event   : EventLogVariant::NftMint(vec![NftMintLog {
-            owner_id : token.owner_id.to_string(),
+            owner_id : receiver_id.to_string(),
             token_ids: vec![token_id.to_string()],
             memo     : None,  // optional
           }]),
@@ -343,34 +757,316 @@ impl Contract {
 
         // calculate required storage
         let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+        let required_cost_to_store_info = env::storage_byte_cost() 
+            * Balance::from(required_storage_in_bytes);
+
+        // refund excess storage if user attached too much. Unlike 
+        // `refund_deposit` we don't panic if it's too little: too late. 
+        let refund = env::attached_deposit().saturating_sub(required_cost_to_store_info);
+
+        if refund > 1 {
+          Promise::new(env::signer_account_id()).transfer(refund);
+        }
+
+        self.internal_record_donation(
+          &env::signer_account_id(), 
+          category_id, 
+          &token_id, 
+          donate_amount.0,
+          anonymous.unwrap_or(false),
+          escrow_campaign_id,
+        );
+
+        true
+    }
 
-        // refund excess storage if user attached too much. 
-        // Panic if they didn't attach enough. 
-        refund_deposit(required_storage_in_bytes, env::signer_account_id());
+
+    /// Resolve `donate_and_mint` once `nft_mint` is done, in a receipt of 
+    /// its own so that nothing here is rolled back if `nft_mint` panicked: 
+    /// the pending donation is cleared either way, and the storage deposit 
+    /// (back with us after a panic) returned to the signer. 
+    /// Returns whether the donation went through and the token was minted. 
+    #[private]
+    pub fn resolve_nft_mint(
+      &mut self,
+      token_id: TokenId,
+      donate_amount: U128,
+      anonymous: bool,
+      escrow_campaign_id: Option<CampaignId>,
+    ) -> bool {
+      self.internal_remove_pending_donation(
+        &env::signer_account_id(), 
+        donate_amount.0 + STORAGE_PER_MINT,
+        anonymous,
+      );
+
+      match env::promise_result(0) {
+        PromiseResult::Successful(value) => {
+          near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false)
+        },
+        // `nft_mint` refunds a failed transfer without panicking, so the 
+        // donation reached the beneficiary; only the deposit is left. 
+        // Unless it's in escrow with us, uncounted: return it too. 
+        _ => {
+          let refund_amount = if escrow_campaign_id.is_some() {
+            donate_amount.0 + STORAGE_PER_MINT
+          } else {
+            STORAGE_PER_MINT
+          };
+
+          self.internal_refund_donation(
+            &env::signer_account_id(),
+            &token_id,
+            refund_amount,
+            anonymous,
+            "Minting failed after the donation went through.",
+          );
+
+          false
+        },
+      }
     }
 
 
+    /// Returns whether the donation transfer succeeded. If not, the 
+    /// donation is taken off the token and refunded to the signer. 
+    /// Must not panic, or the pending donation is never cleared. 
     #[private]
     pub fn on_donate_update(
       &mut self,
       token_id: TokenId,
-      old_donate_amount: f64,
-    ) {
+      donate_amount: U128,
+      anonymous: bool,
+      escrow_campaign_id: Option<CampaignId>,
+    ) -> bool {
+      self.internal_remove_pending_donation(&env::signer_account_id(), donate_amount.0, anonymous);
+
       // If promise failed
       if !is_promise_success() {
-        let mut token = expect_lightweight(
-          self.tokens_by_id.get(&token_id),
-          "This token_id cannot be found."
+        // Take the donation back off, unless the token got burned meanwhile. 
+        if let Some(token) = self.tokens_by_id.get(&token_id) {
+          let donate_amount_after = token.donate_amount.saturating_sub(donate_amount.0);
+          self.internal_set_donate_amount(&token_id, token, donate_amount_after);
+        }
+
+        // A failed transfer returns the money to the contract. 
+        self.internal_refund_donation(
+          &env::signer_account_id(),
+          &token_id,
+          donate_amount.0,
+          anonymous,
+          "Transfer to beneficiary failed.",
         );
-  
-        token.donate_amount = old_donate_amount.to_string();
-  
-        self.tokens_by_id.insert(&token_id, &token);
+
+        return false;
+      }
+
+      let category_id = match self.category_by_token_id.get(&token_id) {
+        Some(category_id) => category_id,
+        None => return true,  // burned meanwhile, nothing left to record it on. 
+      };
+      self.internal_record_donation(
+        &env::signer_account_id(), 
+        category_id, 
+        &token_id, 
+        donate_amount.0,
+        anonymous,
+        escrow_campaign_id,
+      );
+
+      true
+    }
+}
+
+/// A `gift_message` needs a real receiver (not the signer, nor the 
+/// contract holding an anonymous token), and fits in `MAX_GIFT_MESSAGE_LEN`. 
+fn assert_gift_message(receiver_id: &AccountId, gift_message: &Option<String>) {
+  if let Some(message) = gift_message {
+    require!(
+      receiver_id != &env::signer_account_id() && receiver_id != &env::current_account_id(),
+      "A gift message needs a receiver_id other than yourself."
+    );
+    require!(
+      message.len() <= MAX_GIFT_MESSAGE_LEN,
+      format!("Gift message cannot be longer than {} bytes.", MAX_GIFT_MESSAGE_LEN)
+    );
+  }
+}
+
+
+/// Metadata `extra` of a gifted token. No `donor_id` for anonymous gifts. 
+fn gift_extra(donor_id: Option<AccountId>, gift_message: Option<String>) -> String {
+  near_sdk::serde_json::json!({
+    "gift_from": donor_id,
+    "gift_message": gift_message,
+  }).to_string()
+}
+
+
+impl Contract {
+    /// Mint or update the token of every category for `account_id` right 
+    /// here, then transfer the donations, one transfer per beneficiary, 
+    /// resolved by `resolve_batch_donate`. The caller makes sure the 
+    /// donations are paid for; returns the promise and the storage cost. 
+    pub(crate) fn internal_batch_donate(
+      &mut self,
+      account_id: &AccountId,
+      suffix_token_id: &str,
+      hash_of_amounts: HashMap<CategoryId, U128>,
+      issued_at: Option<u64>,
+    ) -> (Promise, Balance) {
+      require!(
+        !hash_of_amounts.is_empty(),
+        "Please donate to at least one category."
+      );
+
+      let initial_storage_usage = env::storage_usage();
+
+      let mut token_id_list: HashMap<CategoryId, TokenId> = self.tokens_per_owner_ordered
+          .get(account_id)
+          .unwrap_or_default();
+
+      let mut total_donation: u128 = 0;
+      let mut minted_token_ids: Vec<TokenId> = Vec::new();
+      let mut donations_per_beneficiary: HashMap<AccountId, Vec<CategoryDonation>> = HashMap::new();
+
+      for (id, amount) in hash_of_amounts {
+        self.internal_assert_accepts_donations(id);
+        let (beneficiary_id, escrow_campaign_id) = self.internal_route_donation(id);
+        total_donation += amount.0;
+
+        let (token_id, is_mint) = self.internal_token_for_category(
+          account_id,
+          &mut token_id_list,
+          id,
+          suffix_token_id,
+          issued_at,
+        );
+
+        let token = self.tokens_by_id.get(&token_id).unwrap();
+        let donate_amount = token.donate_amount + amount.0;
+        self.internal_set_donate_amount(&token_id, token, donate_amount);
+
+        if is_mint {
+          minted_token_ids.push(token_id.clone());
+        }
+
+        donations_per_beneficiary
+            .entry(beneficiary_id)
+            .or_insert_with(Vec::new)
+            .push(CategoryDonation {
+              category_id: id,
+              token_id,
+              deposit: amount,
+              is_mint,
+              escrow_campaign_id,
+            });
+      }
+
+      self.tokens_per_owner_ordered.insert(account_id, &token_id_list);
+
+      // One mint log for every token minted in this batch. 
+      if !minted_token_ids.is_empty() {
+        let nft_mint_log: EventLog = EventLog {
+          standard: NFT_STANDARD_NAME.to_string(),
+          version : NFT_METADATA_SPEC.to_string(),
+          event   : EventLogVariant::NftMint(vec![NftMintLog {
+            owner_id : account_id.to_string(),
+            token_ids: minted_token_ids,
+            memo     : None,
+          }]),
+        };
+
+        env::log_str(&nft_mint_log.to_string());
       }
 
-      // One isn't sure, if transfer failed, supposingly we can't 
-      // transfer back, because the money is not with us yet? 
-      // NEAR Protocol might refund automatically if promise failed? 
+      let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(required_storage_in_bytes);
+
+      self.internal_add_pending_donation(account_id, total_donation, false);
 
+      // The only promises: one transfer per beneficiary. 
+      let mut transfers: Vec<BeneficiaryTransfer> = Vec::new();
+      let mut promises: Option<Promise> = None;
+
+      for (beneficiary_id, donations) in donations_per_beneficiary {
+        let amount: u128 = donations.iter().map(|donation| donation.deposit.0).sum();
+        let promise = Promise::new(beneficiary_id.clone()).transfer(amount);
+
+        promises = Some(match promises {
+          Some(promises) => promises.and(promise),
+          None => promise,
+        });
+
+        transfers.push(BeneficiaryTransfer {
+          beneficiary_id,
+          donations,
+        });
+      }
+
+      let promise = promises.unwrap().then(
+        ext_self::resolve_batch_donate(
+          account_id.clone(),
+          transfers,
+
+          env::current_account_id(),
+          0,
+          GAS_FOR_RESOLVE_BATCH
+        )
+      );
+
+      (promise, required_cost_to_store_info)
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn ordered_map_points_to_the_id_actually_minted() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &friend_id(), ONE_NEAR);
+
+      // as left by `minting_interface`, before the id got taken. 
+      let token_id_list: HashMap<CategoryId, TokenId> = 
+          vec![(0, "animal-donor".to_string())].into_iter().collect();
+      contract.tokens_per_owner_ordered.insert(&donor_id(), &token_id_list);
+
+      assert!(mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR));
+
+      let token_id = ordered_token(&contract, &donor_id()).unwrap();
+      assert_ne!(token_id, "animal-donor");
+      assert_eq!(contract.nft_token(token_id).unwrap().owner_id, donor_id());
+      assert_eq!(ordered_token(&contract, &friend_id()), Some("animal-donor".to_string()));
+    }
+
+    fn gift_through_interface(receiver_id: Option<AccountId>, gift_message: &str) {
+      let mut contract = setup_contract();
+
+      set_caller(&donor_id(), 2 * ONE_NEAR);
+      contract.minting_interface(
+        "-donor".to_string(),
+        vec![(CategoryKey::Id(0), U128(ONE_NEAR))].into_iter().collect(),
+        None,
+        receiver_id,
+        Some(gift_message.to_string()),
+        None,
+      );
+    }
+
+    #[test]
+    #[should_panic(expected = "A gift message needs a receiver_id other than yourself.")]
+    fn interface_rejects_gift_message_without_receiver() {
+      gift_through_interface(None, "for you");
+    }
+
+    #[test]
+    #[should_panic(expected = "Gift message cannot be longer than 280 bytes.")]
+    fn interface_rejects_long_gift_message() {
+      gift_through_interface(Some(friend_id()), &"a".repeat(MAX_GIFT_MESSAGE_LEN + 1));
+    }
+}
@@ -14,6 +14,7 @@ pub trait NonFungibleTokenCore {
       &mut self,
       template_id: Category,
       metadata: TokenMetadata,
+      beneficiary_id: AccountId,
     );
 
     //transfers an NFT to a receiver ID
@@ -92,24 +93,29 @@ trait NonFungibleTokenResolver {
 impl NonFungibleTokenCore for Contract {
 
     /// Generate metadata template so no need to specify in frontend. 
+    /// Donations to this category are forwarded to `beneficiary_id`. 
+    /// Curators only. 
     #[payable]
     fn generate_template(
       &mut self,
       template_id: Category,
       metadata: TokenMetadata,
+      beneficiary_id: AccountId,
     ) {
+      self.internal_assert_not_paused(PauseScope::All);
       let initial_storage_usage = env::storage_usage();
 
       require!(
         env::attached_deposit() >= near_to_yoctonear(0.1),
         "Please attach more than 0.1N for storage. Extra will be refunded."
       );
-      // Require called by authorized people only is skipped. 
-      // We can implement it in the future. 
+      self.internal_assert_role(Role::Curator);
+      self.internal_assert_new_slug(&template_id);
 
       // Since item starts from 0, we find length first before inserting. 
       let category_len = self.categories.len();
       self.categories.push(&template_id);
+      self.category_id_by_slug.insert(&template_id, &(category_len as u16));
 
       // Then update the metadata: 
       self.token_metadata_by_cat_id.insert(
@@ -117,6 +123,14 @@ impl NonFungibleTokenCore for Contract {
         &metadata
       );
 
+      // And who the donations go to. 
+      self.beneficiary_by_cat_id.insert(
+        &(category_len as u16),
+        &beneficiary_id
+      );
+
+      self.internal_log_category_event(category_len as u16, EventLogVariant::CategoryCreate);
+
       // I think we're done. 
       let required_storage = env::storage_usage() - initial_storage_usage;
 
@@ -132,6 +146,9 @@ impl NonFungibleTokenCore for Contract {
         approval_id: u64,
         memo: Option<String>,
     ) {
+        self.internal_assert_not_paused(PauseScope::Transfers);
+        self.internal_assert_transferable(&token_id);
+
         // assert user attached exactly 1 yoctoNEAR. This is for security
         // and that the user will be redirected to the NEAR wallet. 
         assert_one_yocto();
@@ -166,6 +183,8 @@ impl NonFungibleTokenCore for Contract {
         memo: Option<String>,
         msg: String,
     ) -> PromiseOrValue<bool> {
+        self.internal_assert_not_paused(PauseScope::Transfers);
+        self.internal_assert_transferable(&token_id);
         assert_one_yocto();
 
         // assert enough GAS
@@ -226,12 +245,16 @@ impl NonFungibleTokenCore for Contract {
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
         // If there is some token ID in the tokens_by_id collection
         if let Some(token) = self.tokens_by_id.get(&token_id) {
-          let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+          let metadata = self.internal_tiered_metadata(
+            &token_id,
+            token.donate_amount,
+            self.token_metadata_by_id.get(&token_id).unwrap(),
+          );
 
           // return JsonToken (wrapped by Some since it's Option)
           Some(JsonToken {
             token_id,
-            donate_amount: token.donate_amount,
+            donate_amount: U128(token.donate_amount),
             owner_id: token.owner_id,
             metadata,
             approved_account_ids: token.approved_account_ids,
@@ -299,6 +322,7 @@ impl NonFungibleTokenResolver for Contract {
 
         // we change the token struct's owner to the original owner
         token.owner_id = owner_id.clone();
+        self.internal_after_transfer(&token_id, &receiver_id, &owner_id);
 
         // refund approved account IDs may have set on token. 
         refund_approved_account_ids(receiver_id.clone(), &token.approved_account_ids);
@@ -327,4 +351,51 @@ impl NonFungibleTokenResolver for Contract {
         // receiver_id didn't successfully receive the token. 
         false
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    /// `nft_on_transfer` of the receiver asked for the token back. 
+    fn revert_transfer(contract: &mut Contract) -> bool {
+      set_callback(&donor_id(), 0, vec![PromiseResult::Successful(b"true".to_vec())]);
+      contract.nft_resolve_transfer(
+        None,
+        donor_id(),
+        friend_id(),
+        "animal-donor".to_string(),
+        HashMap::new(),
+        None,
+      )
+    }
+
+
+    #[test]
+    fn reverted_transfer_gives_token_and_credit_back() {
+      for policy in [TransferCreditPolicy::StaysWithDonor, TransferCreditPolicy::TravelsWithToken] {
+        let mut contract = setup_transferred(policy);
+
+        assert!(!revert_transfer(&mut contract));
+
+        assert_eq!(contract.nft_token("animal-donor".to_string()).unwrap().owner_id, donor_id());
+        assert_eq!(ordered_token(&contract, &donor_id()), Some("animal-donor".to_string()));
+        assert_eq!(ordered_token(&contract, &friend_id()), None);
+        assert_eq!(credited_donations(&contract, &donor_id()), 1);
+        assert_eq!(credited_donations(&contract, &friend_id()), 0);
+      }
+    }
+
+
+    #[test]
+    fn donor_donates_on_reverted_token() {
+      let mut contract = setup_transferred(TransferCreditPolicy::TravelsWithToken);
+      revert_transfer(&mut contract);
+
+      // the token is theirs again: updated, not minted anew. 
+      donate_through_interface(&mut contract, &donor_id(), ONE_NEAR);
+      assert_eq!(ordered_token(&contract, &donor_id()), Some("animal-donor".to_string()));
+      assert_eq!(ordered_token(&contract, &friend_id()), None);
+    }
+}
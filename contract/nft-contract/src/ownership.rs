@@ -0,0 +1,100 @@
+use crate::*;
+use near_sdk::require;
+
+
+impl Contract {
+    fn internal_log_ownership_event(
+      &self,
+      owner_id: &AccountId,
+      pending_owner_id: &AccountId,
+      variant: fn(Vec<OwnershipLog>) -> EventLogVariant,
+    ) {
+      let ownership_log: EventLog = EventLog {
+        standard: DONATION_STANDARD_NAME.to_string(),
+        version: DONATION_EVENT_VERSION.to_string(),
+        event: variant(vec![OwnershipLog {
+          owner_id: owner_id.to_string(),
+          pending_owner_id: pending_owner_id.to_string(),
+        }]),
+      };
+
+      env::log_str(&ownership_log.to_string());
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Propose `new_owner_id` as owner. Nothing changes until they call 
+    /// `accept_ownership`. Replaces any earlier proposal. Owner only. 
+    #[payable]
+    pub fn propose_owner(&mut self, new_owner_id: AccountId) {
+      assert_one_yocto();
+      self.internal_assert_role(Role::Owner);
+      require!(new_owner_id != self.owner_id, "Already the owner.");
+
+      self.pending_owner_id = Some(new_owner_id.clone());
+      self.internal_log_ownership_event(
+        &self.owner_id, 
+        &new_owner_id, 
+        EventLogVariant::OwnershipPropose
+      );
+    }
+
+
+    /// Become the owner, if proposed. The only way `owner_id` changes. 
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+      assert_one_yocto();
+
+      let new_owner_id = env::predecessor_account_id();
+      require!(
+        self.pending_owner_id.as_ref() == Some(&new_owner_id),
+        "You weren't proposed as owner."
+      );
+
+      let old_owner_id = std::mem::replace(&mut self.owner_id, new_owner_id.clone());
+      self.pending_owner_id = None;
+
+      self.internal_log_ownership_event(
+        &old_owner_id, 
+        &new_owner_id, 
+        EventLogVariant::OwnershipAccept
+      );
+    }
+
+
+    /// Withdraw the proposal. The owner or the proposed owner can do this. 
+    #[payable]
+    pub fn cancel_ownership_proposal(&mut self) {
+      assert_one_yocto();
+
+      let pending_owner_id = expect_lightweight(
+        self.pending_owner_id.clone(),
+        "No ownership proposal to cancel."
+      );
+      require!(
+        env::predecessor_account_id() == self.owner_id 
+        || env::predecessor_account_id() == pending_owner_id,
+        "Only the owner or the proposed owner can cancel."
+      );
+
+      self.pending_owner_id = None;
+      self.internal_log_ownership_event(
+        &self.owner_id, 
+        &pending_owner_id, 
+        EventLogVariant::OwnershipCancel
+      );
+    }
+
+
+    pub fn get_owner(&self) -> AccountId {
+      self.owner_id.clone()
+    }
+
+
+    /// Who was proposed as owner and hasn't accepted yet. 
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+      self.pending_owner_id.clone()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::*;
+use near_sdk::require;
+
+
+/// What a pause applies to. `All` stops every guarded entry point. 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    All,
+    Donations,  // minting, donating, pledges and fungible token donations
+    Transfers,
+    Approvals,  // `nft_approve` only: revoking stays possible
+}
+
+/// Which switches are on. 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseState {
+    pub all: bool,
+    pub donations: bool,
+    pub transfers: bool,
+    pub approvals: bool,
+}
+
+
+impl PauseState {
+    fn switch(&mut self, scope: PauseScope) -> &mut bool {
+      match scope {
+        PauseScope::All => &mut self.all,
+        PauseScope::Donations => &mut self.donations,
+        PauseScope::Transfers => &mut self.transfers,
+        PauseScope::Approvals => &mut self.approvals,
+      }
+    }
+}
+
+
+impl Contract {
+    /// panic if `scope`, or the whole contract, is paused. 
+    pub(crate) fn internal_assert_not_paused(&self, scope: PauseScope) {
+      require!(!self.pause_state.all, "Contract is paused.");
+
+      let paused = match scope {
+        PauseScope::All => false,
+        PauseScope::Donations => self.pause_state.donations,
+        PauseScope::Transfers => self.pause_state.transfers,
+        PauseScope::Approvals => self.pause_state.approvals,
+      };
+
+      require!(!paused, "This is paused for now.");
+    }
+
+    fn internal_set_paused(&mut self, scope: PauseScope, paused: bool) {
+      self.internal_assert_role(Role::Pauser);
+
+      let switch = self.pause_state.switch(scope);
+      require!(*switch != paused, if paused { "Already paused." } else { "Not paused." });
+      *switch = paused;
+
+      let pause_log = PauseLog {
+        scope: near_sdk::serde_json::to_string(&scope).unwrap().trim_matches('"').to_string(),
+        pauser_id: env::predecessor_account_id().to_string(),
+      };
+
+      let contract_pause_log: EventLog = EventLog {
+        standard: DONATION_STANDARD_NAME.to_string(),
+        version: DONATION_EVENT_VERSION.to_string(),
+        event: if paused {
+          EventLogVariant::ContractPause(vec![pause_log])
+        } else {
+          EventLogVariant::ContractUnpause(vec![pause_log])
+        },
+      };
+
+      env::log_str(&contract_pause_log.to_string());
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Stop `scope` until unpaused. Pausers only. 
+    pub fn pause(&mut self, scope: PauseScope) {
+      self.internal_set_paused(scope, true);
+    }
+
+
+    /// Pausers only. 
+    pub fn unpause(&mut self, scope: PauseScope) {
+      self.internal_set_paused(scope, false);
+    }
+
+
+    pub fn get_pause_state(&self) -> &PauseState {
+      &self.pause_state
+    }
+}
@@ -0,0 +1,383 @@
+use crate::*;
+use near_sdk::require;
+
+/// Longest `suffix_token_id` of a pledge, so its mints fit their reserve. 
+const MAX_PLEDGE_SUFFIX_LEN: usize = 64;
+/// Storage of a mint besides its token ID and metadata: collection 
+/// entries, owner, approvals and storage payer. Generous on purpose. 
+const MINT_OVERHEAD_BYTES: u64 = 1_000;
+/// How often a mint stores the token ID, across collections. 
+const TOKEN_ID_COPIES: u64 = 10;
+
+
+/// A recurring donation: `hash_of_amounts` every `interval` nanoseconds, 
+/// `periods_left` more times, paid out of the prepaid `balance`. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Pledge {
+    pub hash_of_amounts: HashMap<CategoryId, Balance>,
+    pub suffix_token_id: String,
+    pub interval: u64,
+    pub periods_left: u32,
+    pub balance: Balance,
+
+    // block timestamp (nanoseconds) of next donation. 
+    pub next_due_at: u64,
+}
+
+/// The Json pledge is what will be returned from view calls. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonPledge {
+    pub account_id: AccountId,
+    pub hash_of_amounts: HashMap<CategoryId, U128>,
+    pub suffix_token_id: String,
+    pub interval: u64,
+    pub periods_left: u32,
+    pub balance: U128,
+    pub next_due_at: u64,
+}
+
+
+impl Pledge {
+    fn amount_per_period(&self) -> Balance {
+      self.hash_of_amounts.values().sum()
+    }
+
+    fn to_json(&self, account_id: AccountId) -> JsonPledge {
+      JsonPledge {
+        account_id,
+        hash_of_amounts: self.hash_of_amounts
+            .iter()
+            .map(|(category_id, amount)| (*category_id, U128(*amount)))
+            .collect(),
+        suffix_token_id: self.suffix_token_id.clone(),
+        interval: self.interval,
+        periods_left: self.periods_left,
+        balance: U128(self.balance),
+        next_due_at: self.next_due_at,
+      }
+    }
+}
+
+
+impl Contract {
+    /// Categories of a pledge the pledger has no token for, to be minted. 
+    fn internal_pledge_mints(&self, account_id: &AccountId, pledge: &Pledge) -> Vec<CategoryId> {
+      let token_id_list = self.tokens_per_owner_ordered.get(account_id).unwrap_or_default();
+
+      pledge.hash_of_amounts
+          .keys()
+          .filter(|category_id| {
+            token_id_list
+                .get(category_id)
+                .map_or(true, |token_id| self.tokens_by_id.get(token_id).is_none())
+          })
+          .cloned()
+          .collect()
+    }
+
+    /// Storage to set aside for a pledge: a mint (at most `STORAGE_PER_MINT`) 
+    /// for every category the pledger has no token for. 
+    fn internal_pledge_storage_reserve(&self, account_id: &AccountId, pledge: &Pledge) -> Balance {
+      STORAGE_PER_MINT * self.internal_pledge_mints(account_id, pledge).len() as u128
+    }
+
+    /// Upper estimate of the storage the next period's mints take. Unlike 
+    /// the reserve, this depends on the category templates, which curators 
+    /// may have grown since the pledge was made. 
+    fn internal_pledge_mint_cost(&self, account_id: &AccountId, pledge: &Pledge) -> Balance {
+      let bytes: u64 = self.internal_pledge_mints(account_id, pledge)
+          .into_iter()
+          .map(|category_id| {
+            let prefix_len = self.categories
+                .get(category_id as u64)
+                .map_or(0, |prefix| prefix.len());
+            let metadata_len = self.token_metadata_by_cat_id
+                .get(&category_id)
+                .map_or(0, |metadata| metadata.try_to_vec().unwrap().len());
+
+            MINT_OVERHEAD_BYTES 
+                + TOKEN_ID_COPIES * (prefix_len + pledge.suffix_token_id.len()) as u64 
+                + metadata_len as u64
+          })
+          .sum();
+
+      env::storage_byte_cost() * Balance::from(bytes)
+    }
+
+    /// What a pledge needs to execute its next period. 
+    fn internal_pledge_period_cost(&self, account_id: &AccountId, pledge: &Pledge) -> Balance {
+      pledge.amount_per_period() + self.internal_pledge_storage_reserve(account_id, pledge)
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Pledge to donate `hash_of_amounts` every `interval` nanoseconds for 
+    /// `periods` periods, starting now. Attach at least all periods plus 
+    /// storage, including 0.1N per category you have no token for yet: 
+    /// tokens minted later are paid from the balance (mostly refunded). 
+    #[payable]
+    pub fn create_pledge(
+      &mut self,
+      suffix_token_id: String,
+      hash_of_amounts: HashMap<CategoryKey, U128>,
+      interval: u64,
+      periods: u32,
+    ) {
+      self.internal_assert_not_paused(PauseScope::Donations);
+      let initial_storage_usage = env::storage_usage();
+      let account_id = env::predecessor_account_id();
+
+      require!(
+        self.pledges.get(&account_id).is_none(),
+        "You already have a pledge. Cancel it first."
+      );
+      require!(!hash_of_amounts.is_empty(), "Please donate to at least one category.");
+      let hash_of_amounts = self.internal_category_amounts(hash_of_amounts);
+      require!(interval > 0, "Interval must be more than 0.");
+      require!(periods > 0, "Must pledge at least one period.");
+      require!(
+        suffix_token_id.len() <= MAX_PLEDGE_SUFFIX_LEN,
+        format!("Suffix cannot be longer than {} bytes.", MAX_PLEDGE_SUFFIX_LEN)
+      );
+
+      for category_id in hash_of_amounts.keys() {
+        self.internal_beneficiary(*category_id);
+      }
+
+      let mut pledge = Pledge {
+        hash_of_amounts: hash_of_amounts
+            .into_iter()
+            .map(|(category_id, amount)| (category_id, amount.0))
+            .collect(),
+        suffix_token_id,
+        interval,
+        periods_left: periods,
+        balance: 0,
+        next_due_at: env::block_timestamp(),
+      };
+
+      self.pledges.insert(&account_id, &pledge);
+
+      let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(required_storage_in_bytes);
+      let required_balance = pledge.amount_per_period() * periods as u128 
+          + self.internal_pledge_storage_reserve(&account_id, &pledge);
+
+      require!(
+        env::attached_deposit() >= required_balance + required_cost_to_store_info,
+        format!(
+          "Must attach {} yoctoNEAR to cover all periods and storage",
+          required_balance + required_cost_to_store_info
+        )
+      );
+
+      pledge.balance = env::attached_deposit() - required_cost_to_store_info;
+      self.pledges.insert(&account_id, &pledge);
+    }
+
+
+    /// Execute up to `limit` due pledges, like `batch_donate` on behalf 
+    /// of each pledger. Anyone (a keeper) can call this. 
+    /// Returns how many pledges were executed. 
+    pub fn execute_due_pledges(&mut self, limit: u64) -> u64 {
+      self.internal_assert_not_paused(PauseScope::Donations);
+      let len = self.pledges.len();
+      let now = env::block_timestamp();
+      let mut executed: u64 = 0;
+
+      // Continue from where the last keeper stopped, so everyone gets a turn. 
+      let start = if self.pledge_cursor < len { self.pledge_cursor } else { 0 };
+      let mut checked: u64 = 0;
+
+      while checked < len && executed < limit {
+        let account_id = self.pledges.keys_as_vector().get((start + checked) % len).unwrap();
+        checked += 1;
+
+        let mut pledge = self.pledges.get(&account_id).unwrap();
+        let amount_per_period = pledge.amount_per_period();
+
+        // Storage of the tokens to mint must be covered too, not only donations. 
+        if pledge.periods_left == 0 
+            || pledge.next_due_at > now 
+            || pledge.balance < self.internal_pledge_period_cost(&account_id, &pledge) 
+        {
+          continue;
+        }
+
+        // Skip (and keep the balance) while a category doesn't take donations. 
+        if !pledge.hash_of_amounts.keys().all(|category_id| self.internal_accepts_donations(*category_id)) {
+          continue;
+        }
+
+        // Mints that won't fit their reserve would never execute: stop the 
+        // pledge, so the pledger can withdraw, rather than retry it forever. 
+        if self.internal_pledge_mint_cost(&account_id, &pledge) 
+            > self.internal_pledge_storage_reserve(&account_id, &pledge) 
+        {
+          pledge.periods_left = 0;
+          self.pledges.insert(&account_id, &pledge);
+          continue;
+        }
+
+        let (_, required_cost_to_store_info) = self.internal_batch_donate(
+          &account_id,
+          &pledge.suffix_token_id,
+          pledge.hash_of_amounts
+              .iter()
+              .map(|(category_id, amount)| (*category_id, U128(*amount)))
+              .collect(),
+          None,
+        );
+
+        // Checked above with a generous estimate. Should the mints still 
+        // cost more than the whole balance, the pledge stops there instead 
+        // of panicking, which would block every pledge after it. 
+        match pledge.balance.checked_sub(amount_per_period + required_cost_to_store_info) {
+          Some(balance) => {
+            pledge.balance = balance;
+            pledge.periods_left -= 1;
+          },
+          None => {
+            pledge.balance = 0;
+            pledge.periods_left = 0;
+          },
+        }
+        pledge.next_due_at += pledge.interval;
+
+        self.pledges.insert(&account_id, &pledge);
+        executed += 1;
+      }
+
+      self.pledge_cursor = if len == 0 { 0 } else { (start + checked) % len };
+
+      executed
+    }
+
+
+    /// Stop a pledge. The balance stays until withdrawn. 
+    pub fn cancel_pledge(&mut self) {
+      let account_id = env::predecessor_account_id();
+      let mut pledge = expect_lightweight(
+        self.pledges.get(&account_id),
+        "You don't have a pledge."
+      );
+
+      pledge.periods_left = 0;
+      self.pledges.insert(&account_id, &pledge);
+    }
+
+
+    /// Withdraw the pledge balance not needed for the periods left, 
+    /// storage of tokens still to mint included. 
+    /// A cancelled or completed pledge is removed. 
+    pub fn withdraw_pledge_balance(&mut self) -> U128 {
+      let account_id = env::predecessor_account_id();
+      let mut pledge = expect_lightweight(
+        self.pledges.get(&account_id),
+        "You don't have a pledge."
+      );
+
+      let reserved = if pledge.periods_left == 0 {
+        0
+      } else {
+        pledge.amount_per_period() * pledge.periods_left as u128 
+            + self.internal_pledge_storage_reserve(&account_id, &pledge)
+      };
+      let withdraw_amount = pledge.balance.saturating_sub(reserved);
+
+      if pledge.periods_left == 0 {
+        self.pledges.remove(&account_id);
+      } else {
+        pledge.balance -= withdraw_amount;
+        self.pledges.insert(&account_id, &pledge);
+      }
+
+      if withdraw_amount > 0 {
+        Promise::new(account_id).transfer(withdraw_amount);
+      }
+
+      U128(withdraw_amount)
+    }
+
+
+    pub fn get_pledge(&self, account_id: AccountId) -> Option<JsonPledge> {
+      self.pledges.get(&account_id).map(|pledge| pledge.to_json(account_id))
+    }
+
+
+    /// Pledges that could be executed now. 
+    pub fn get_due_pledges(
+      &self,
+      from_index: Option<U128>,
+      limit: Option<u64>,
+    ) -> Vec<JsonPledge> {
+      let now = env::block_timestamp();
+      let start = u128::from(from_index.unwrap_or(U128(0)));
+
+      self.pledges
+          .iter()
+          .filter(|(account_id, pledge)| {
+            pledge.periods_left > 0 
+                && pledge.next_due_at <= now 
+                && pledge.balance >= self.internal_pledge_period_cost(account_id, pledge)
+          })
+          .skip(start as usize)
+          .take(limit.unwrap_or(0) as usize)
+          .map(|(account_id, pledge)| pledge.to_json(account_id))
+          .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn create_pledge(contract: &mut Contract, account_id: &AccountId, category: &str, suffix: &str) {
+      set_caller(account_id, 2 * ONE_NEAR);
+      contract.create_pledge(
+        suffix.to_string(),
+        vec![(CategoryKey::Slug(category.to_string()), U128(ONE_NEAR / 10))].into_iter().collect(),
+        1,
+        3,
+      );
+    }
+
+
+    #[test]
+    #[should_panic(expected = "Suffix cannot be longer than 64 bytes.")]
+    fn long_suffix_is_rejected() {
+      let mut contract = setup_contract();
+      create_pledge(&mut contract, &donor_id(), "animal", &"x".repeat(65));
+    }
+
+
+    #[test]
+    fn oversized_mint_stops_pledge_without_blocking_others() {
+      let mut contract = setup_contract();
+
+      set_caller(&owner_id(), ONE_NEAR);
+      contract.generate_template("plant".to_string(), sample_metadata("Plants"), beneficiary_id());
+
+      create_pledge(&mut contract, &donor_id(), "animal", "-donor");
+      create_pledge(&mut contract, &friend_id(), "plant", "-friend");
+
+      // tokens of "animal" no longer fit their reserve. 
+      let mut metadata = sample_metadata("Animal Welfare");
+      metadata.description = Some("x".repeat(20_000));
+      set_caller(&owner_id(), ONE_NEAR);
+      contract.update_category(CategoryKey::Id(0), metadata);
+
+      set_caller(&owner_id(), 0);
+      assert_eq!(contract.execute_due_pledges(10), 1);
+
+      assert_eq!(contract.get_pledge(donor_id()).unwrap().periods_left, 0);
+      assert_eq!(contract.get_pledge(friend_id()).unwrap().periods_left, 2);
+      assert_eq!(ordered_token(&contract, &donor_id()), None);
+    }
+}
@@ -0,0 +1,349 @@
+use crate::*;
+use near_sdk::require;
+
+pub type RoundId = u64;
+
+/// Contributions are square-rooted in milliNEAR, so smaller donations 
+/// don't count towards matching and the weights don't overflow. 
+const QF_UNIT: Balance = 1_000_000_000_000_000_000_000;
+/// Precision of a category's share of the pot. 
+const SHARE_PRECISION: u128 = 1_000_000_000;
+
+
+/// A quadratic funding round: donations to `category_ids` between 
+/// `starts_at` and `ends_at` (block timestamp, nanoseconds) share `pot`. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct QfRound {
+    pub creator_id: AccountId,
+    pub category_ids: Vec<CategoryId>,
+    pub pot: Balance,
+    pub starts_at: u64,
+    pub ends_at: u64,
+
+    // filled in by `finalize_qf_round`. 
+    pub finalized: bool,
+    pub matches: HashMap<CategoryId, Balance>,
+}
+
+/// Running totals of a category during a round. 
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct QfTally {
+    pub total: Balance,
+    pub donors: u64,
+
+    // sum over donors of sqrt(their total, in QF_UNIT). 
+    pub sum_sqrt: u128,
+}
+
+/// The Json round is what will be returned from view calls. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonQfRound {
+    pub round_id: RoundId,
+    pub creator_id: AccountId,
+    pub category_ids: Vec<CategoryId>,
+    pub pot: U128,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    pub finalized: bool,
+    pub matches: HashMap<CategoryId, U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonQfTally {
+    pub total: U128,
+    pub donors: u64,
+}
+
+
+/// Integer square root (rounded down). 
+fn integer_sqrt(value: u128) -> u128 {
+  if value < 2 {
+    return value;
+  }
+
+  let mut x = value;
+  let mut y = (x + 1) / 2;
+
+  while y < x {
+    x = y;
+    y = (x + value / x) / 2;
+  }
+
+  x
+}
+
+
+impl Contract {
+    /// Quadratic funding match of each category of a round if it 
+    /// were finalized with the current tallies. 
+    pub(crate) fn internal_qf_matches(&self, round_id: RoundId) -> HashMap<CategoryId, Balance> {
+      let round = expect_lightweight(
+        self.qf_rounds.get(round_id),
+        "Cannot find this round."
+      );
+
+      // weight = (sum of sqrt)^2 - sum, all in QF_UNIT. 
+      let weights: Vec<(CategoryId, u128)> = round.category_ids
+          .iter()
+          .map(|category_id| {
+            let tally = self.qf_tallies.get(&(round_id, *category_id)).unwrap_or_default();
+            let weight = (tally.sum_sqrt * tally.sum_sqrt).saturating_sub(tally.total / QF_UNIT);
+
+            (*category_id, weight)
+          })
+          .collect();
+
+      let total_weight: u128 = weights.iter().map(|(_, weight)| weight).sum();
+      let mut matches = HashMap::new();
+
+      for (category_id, weight) in weights {
+        let matched = if total_weight == 0 {
+          0
+        } else {
+          (round.pot / SHARE_PRECISION) * (weight * SHARE_PRECISION / total_weight)
+        };
+
+        matches.insert(category_id, matched);
+      }
+
+      matches
+    }
+
+    /// Count a donation towards every running round of its category. 
+    pub(crate) fn internal_tally_qf(
+      &mut self,
+      account_id: &AccountId,
+      category_id: CategoryId,
+      donate_amount: Balance,
+    ) {
+      let round_ids = self.qf_rounds_by_cat_id.get(&category_id).unwrap_or_default();
+      let now = env::block_timestamp();
+
+      for round_id in round_ids {
+        let round = self.qf_rounds.get(round_id).unwrap();
+
+        if now < round.starts_at || now >= round.ends_at {
+          continue;
+        }
+
+        let key = (round_id, category_id, account_id.clone());
+        let old_contribution = self.qf_contributions.get(&key).unwrap_or(0);
+        let new_contribution = old_contribution + donate_amount;
+
+        let mut tally = self.qf_tallies.get(&(round_id, category_id)).unwrap_or_default();
+
+        if old_contribution == 0 {
+          tally.donors += 1;
+        }
+
+        tally.total += donate_amount;
+        tally.sum_sqrt = tally.sum_sqrt 
+            + integer_sqrt(new_contribution / QF_UNIT) 
+            - integer_sqrt(old_contribution / QF_UNIT);
+
+        self.qf_contributions.insert(&key, &new_contribution);
+        self.qf_tallies.insert(&(round_id, category_id), &tally);
+      }
+    }
+
+    /// Take a refunded donation, made at `timestamp`, back off the 
+    /// running rounds it was counted towards. Finalized rounds paid out 
+    /// already and are left alone. 
+    pub(crate) fn internal_untally_qf(
+      &mut self,
+      account_id: &AccountId,
+      category_id: CategoryId,
+      donate_amount: Balance,
+      timestamp: u64,
+    ) {
+      let round_ids = self.qf_rounds_by_cat_id.get(&category_id).unwrap_or_default();
+
+      for round_id in round_ids {
+        let round = self.qf_rounds.get(round_id).unwrap();
+
+        if timestamp < round.starts_at || timestamp >= round.ends_at {
+          continue;
+        }
+
+        let key = (round_id, category_id, account_id.clone());
+        let old_contribution = self.qf_contributions.get(&key).unwrap_or(0);
+
+        if old_contribution == 0 {
+          continue;
+        }
+
+        let new_contribution = old_contribution.saturating_sub(donate_amount);
+        let mut tally = self.qf_tallies.get(&(round_id, category_id)).unwrap_or_default();
+
+        if new_contribution == 0 {
+          tally.donors -= 1;
+          self.qf_contributions.remove(&key);
+        } else {
+          self.qf_contributions.insert(&key, &new_contribution);
+        }
+
+        tally.total -= old_contribution - new_contribution;
+        tally.sum_sqrt = tally.sum_sqrt 
+            - integer_sqrt(old_contribution / QF_UNIT) 
+            + integer_sqrt(new_contribution / QF_UNIT);
+
+        self.qf_tallies.insert(&(round_id, category_id), &tally);
+      }
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Start a quadratic funding round over `category_ids`. 
+    /// Attach the matching pot plus storage. Treasurers only. 
+    #[payable]
+    pub fn create_qf_round(
+      &mut self,
+      category_ids: Vec<CategoryKey>,
+      pot: U128,
+      starts_at: u64,
+      ends_at: u64,
+    ) -> RoundId {
+      let initial_storage_usage = env::storage_usage();
+
+      self.internal_assert_role(Role::Treasurer);
+      require!(!category_ids.is_empty(), "Round must have at least one category.");
+      let category_ids = self.internal_category_ids(category_ids);
+      require!(starts_at < ends_at, "Round must start before it ends.");
+      require!(
+        ends_at > env::block_timestamp(),
+        "Round must end in the future."
+      );
+
+      for category_id in category_ids.iter() {
+        require!(
+          self.beneficiary_by_cat_id.get(category_id).is_some(),
+          "Cannot find category. Please contact support."
+        );
+      }
+
+      let round_id = self.qf_rounds.len();
+
+      self.qf_rounds.push(&QfRound {
+        creator_id: env::predecessor_account_id(),
+        category_ids: category_ids.clone(),
+        pot: pot.0,
+        starts_at,
+        ends_at,
+        finalized: false,
+        matches: HashMap::new(),
+      });
+
+      for category_id in category_ids {
+        let mut round_ids = self.qf_rounds_by_cat_id.get(&category_id).unwrap_or_default();
+        round_ids.push(round_id);
+        self.qf_rounds_by_cat_id.insert(&category_id, &round_ids);
+      }
+
+      let required_storage_in_bytes = env::storage_usage() - initial_storage_usage;
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(required_storage_in_bytes);
+
+      require!(
+        env::attached_deposit() >= pot.0 + required_cost_to_store_info,
+        format!(
+          "Must attach {} yoctoNEAR to cover pot and storage",
+          pot.0 + required_cost_to_store_info
+        )
+      );
+
+      let refund = env::attached_deposit() - pot.0 - required_cost_to_store_info;
+
+      if refund > 1 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+      }
+
+      round_id
+    }
+
+
+    /// Pay out the matches of an ended round to the beneficiaries, and 
+    /// what's left of the pot back to its creator. Anyone can call this. 
+    pub fn finalize_qf_round(&mut self, round_id: RoundId) -> HashMap<CategoryId, U128> {
+      let mut round = expect_lightweight(
+        self.qf_rounds.get(round_id),
+        "Cannot find this round."
+      );
+
+      require!(!round.finalized, "Round already finalized.");
+      require!(
+        env::block_timestamp() >= round.ends_at,
+        "Round hasn't ended yet."
+      );
+
+      let matches = self.internal_qf_matches(round_id);
+      let mut paid: Balance = 0;
+
+      for (category_id, matched) in matches.iter() {
+        if *matched > 0 {
+          Promise::new(self.internal_beneficiary(*category_id)).transfer(*matched);
+          paid += matched;
+        }
+
+        let mut round_ids = self.qf_rounds_by_cat_id.get(category_id).unwrap_or_default();
+        round_ids.retain(|id| *id != round_id);
+        self.qf_rounds_by_cat_id.insert(category_id, &round_ids);
+      }
+
+      let leftover = round.pot - paid;
+
+      if leftover > 0 {
+        Promise::new(round.creator_id.clone()).transfer(leftover);
+      }
+
+      round.finalized = true;
+      round.matches = matches;
+      self.qf_rounds.replace(round_id, &round);
+
+      round.matches
+          .into_iter()
+          .map(|(category_id, matched)| (category_id, U128(matched)))
+          .collect()
+    }
+
+
+    pub fn get_qf_round(&self, round_id: RoundId) -> Option<JsonQfRound> {
+      self.qf_rounds.get(round_id).map(|round| JsonQfRound {
+        round_id,
+        creator_id: round.creator_id,
+        category_ids: round.category_ids,
+        pot: U128(round.pot),
+        starts_at: round.starts_at,
+        ends_at: round.ends_at,
+        finalized: round.finalized,
+        matches: round.matches
+            .into_iter()
+            .map(|(category_id, matched)| (category_id, U128(matched)))
+            .collect(),
+      })
+    }
+
+
+    /// Donations and unique donors of a category in a round so far. 
+    pub fn get_qf_tally(&self, round_id: RoundId, category_id: CategoryKey) -> JsonQfTally {
+      let category_id = self.internal_category_id(&category_id);
+      let tally = self.qf_tallies.get(&(round_id, category_id)).unwrap_or_default();
+
+      JsonQfTally {
+        total: U128(tally.total),
+        donors: tally.donors,
+      }
+    }
+
+
+    /// What each category would be matched if the round ended now. 
+    pub fn get_projected_qf_matches(&self, round_id: RoundId) -> HashMap<CategoryId, U128> {
+      self.internal_qf_matches(round_id)
+          .into_iter()
+          .map(|(category_id, matched)| (category_id, U128(matched)))
+          .collect()
+    }
+}
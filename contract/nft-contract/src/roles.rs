@@ -0,0 +1,105 @@
+use crate::*;
+use near_sdk::require;
+
+
+/// What an account is allowed to administer. `owner_id` has every role; 
+/// the `Owner` role itself cannot be granted. 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Curator,    // categories and campaigns
+    Treasurer,  // accepted tokens and funding rounds
+    Pauser,
+}
+
+const GRANTABLE_ROLES: [Role; 3] = [Role::Curator, Role::Treasurer, Role::Pauser];
+
+
+impl Contract {
+    pub(crate) fn internal_has_role(&self, account_id: &AccountId, role: Role) -> bool {
+      if account_id == &self.owner_id {
+        return true;
+      }
+
+      role != Role::Owner && self.role_members
+          .get(&role)
+          .map_or(false, |members| members.contains(account_id))
+    }
+
+    /// Panic unless the predecessor has `role`. 
+    pub(crate) fn internal_assert_role(&self, role: Role) {
+      require!(
+        self.internal_has_role(&env::predecessor_account_id(), role),
+        format!(
+          "Only the owner or a {} can do this.", 
+          near_sdk::serde_json::to_string(&role).unwrap().trim_matches('"')
+        )
+      );
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Give `account_id` a role. Owner only. 
+    pub fn grant_role(&mut self, account_id: AccountId, role: Role) {
+      self.internal_assert_role(Role::Owner);
+      require!(role != Role::Owner, "Ownership cannot be granted.");
+
+      let mut members = self.role_members.get(&role).unwrap_or_else(|| {
+        UnorderedSet::new(
+          StorageKey::RoleMembersInner { role }
+          .try_to_vec()
+          .unwrap(),
+        )
+      });
+
+      members.insert(&account_id);
+      self.role_members.insert(&role, &members);
+    }
+
+
+    /// Take a role away from `account_id`. Owner only. 
+    pub fn revoke_role(&mut self, account_id: AccountId, role: Role) {
+      self.internal_assert_role(Role::Owner);
+      require!(role != Role::Owner, "Ownership cannot be revoked.");
+
+      if let Some(mut members) = self.role_members.get(&role) {
+        members.remove(&account_id);
+        self.role_members.insert(&role, &members);
+      }
+    }
+
+
+    /// Accounts granted `role`. For `Owner`, just the owner. 
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+      if role == Role::Owner {
+        return vec![self.owner_id.clone()];
+      }
+
+      self.role_members
+          .get(&role)
+          .map_or(vec![], |members| members.to_vec())
+    }
+
+
+    /// Roles `account_id` has, the owner having them all. 
+    pub fn get_roles(&self, account_id: AccountId) -> Vec<Role> {
+      if account_id == self.owner_id {
+        return vec![Role::Owner, Role::Curator, Role::Treasurer, Role::Pauser];
+      }
+
+      GRANTABLE_ROLES
+          .iter()
+          .filter(|role| self.internal_has_role(&account_id, **role))
+          .copied()
+          .collect()
+    }
+
+
+    pub fn has_role(&self, account_id: AccountId, role: Role) -> bool {
+      self.internal_has_role(&account_id, role)
+    }
+}
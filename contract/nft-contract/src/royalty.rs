@@ -71,6 +71,8 @@ impl NonFungibleTokenCore for Contract {
         balance: U128,
         max_len_payout: u16,
     ) -> Payout {
+        self.internal_assert_not_paused(PauseScope::Transfers);
+        self.internal_assert_transferable(&token_id);
         assert_one_yocto();
         
         let sender_id = env::predecessor_account_id();
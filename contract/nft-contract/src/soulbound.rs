@@ -0,0 +1,92 @@
+use crate::*;
+use near_sdk::require;
+
+
+impl Contract {
+    /// Whether a token may change hands: not when the whole contract, 
+    /// or the token's category, is soulbound. 
+    pub(crate) fn internal_is_transferable(&self, token_id: &TokenId) -> bool {
+      if self.soulbound {
+        return false;
+      }
+
+      self.category_by_token_id
+          .get(token_id)
+          .map_or(true, |category_id| !self.soulbound_categories.contains(&category_id))
+    }
+
+    pub(crate) fn internal_assert_transferable(&self, token_id: &TokenId) {
+      require!(
+        self.internal_is_transferable(token_id),
+        "This token is soulbound: donation receipts cannot be transferred."
+      );
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Make every token soulbound, or stop doing so. Owner only. 
+    pub fn set_soulbound(&mut self, soulbound: bool) {
+      self.internal_assert_role(Role::Owner);
+      self.soulbound = soulbound;
+    }
+
+
+    /// Make the tokens of a category soulbound, or stop doing so. 
+    /// Curators only. 
+    pub fn set_category_soulbound(&mut self, category_id: CategoryKey, soulbound: bool) {
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+
+      if soulbound {
+        self.soulbound_categories.insert(&category_id);
+      } else {
+        self.soulbound_categories.remove(&category_id);
+      }
+    }
+
+
+    pub fn is_soulbound(&self) -> bool {
+      self.soulbound
+    }
+
+
+    pub fn get_soulbound_categories(&self) -> Vec<CategoryId> {
+      self.soulbound_categories.to_vec()
+    }
+
+
+    /// Whether `nft_transfer` and friends would let this token move. 
+    /// False for a token that doesn't exist. 
+    pub fn is_transferable(&self, token_id: TokenId) -> bool {
+      self.tokens_by_id.contains_key(&token_id) && self.internal_is_transferable(&token_id)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn missing_token_is_not_transferable() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+      assert!(contract.is_transferable("animal-donor".to_string()));
+      assert!(!contract.is_transferable("animal-nobody".to_string()));
+    }
+
+    #[test]
+    fn soulbound_category_is_not_transferable() {
+      let mut contract = setup_contract();
+      mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+      set_caller(&owner_id(), 0);
+      contract.set_category_soulbound(CategoryKey::Id(0), true);
+
+      assert!(!contract.is_transferable("animal-donor".to_string()));
+    }
+}
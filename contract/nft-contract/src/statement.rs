@@ -0,0 +1,112 @@
+use crate::*;
+
+
+/// Total given to one category, in one asset, within a statement. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StatementTotal {
+    pub category_id: CategoryId,
+    pub beneficiary_id: Option<AccountId>,
+    pub ft_contract_id: Option<AccountId>,  // None for NEAR
+    pub amount: U128,
+    pub donations: u64,
+}
+
+/// Statement of giving of an account over `[from_timestamp, to_timestamp)`
+/// (block timestamp, nanoseconds). 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonDonationStatement {
+    pub account_id: AccountId,
+    pub from_timestamp: u64,
+    pub to_timestamp: u64,
+    pub totals: Vec<StatementTotal>,
+    pub lines: Vec<JsonDonationRecord>,
+
+    // hex sha256 over the borsh encoding of the account, range and lines, 
+    // so the same statement always gives the same hash. 
+    pub statement_hash: String,
+}
+
+
+fn to_hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Every donation credited to `account_id` within the time range, 
+    /// with per category totals. Donations refunded from a campaign's 
+    /// escrow are left out. 
+    pub fn get_donation_statement(
+      &self,
+      account_id: AccountId,
+      from_timestamp: u64,
+      to_timestamp: u64,
+    ) -> JsonDonationStatement {
+      let mut lines: Vec<JsonDonationRecord> = vec![];
+      let mut totals: Vec<StatementTotal> = vec![];
+      let mut hashed: Vec<(DonationIndex, TokenId, CategoryId, Balance, Option<AccountId>, u64)> = vec![];
+
+      if let Some(indexes) = self.ledger_by_account.get(&account_id) {
+        for index in indexes.iter() {
+          if self.refunded_donations.contains(&index) {
+            continue;
+          }
+
+          let record = self.donation_ledger.get(index).unwrap();
+
+          // revealed anonymous donations are indexed late, so 
+          // don't assume timestamps are in order. 
+          if record.timestamp < from_timestamp || record.timestamp >= to_timestamp {
+            continue;
+          }
+
+          match totals.iter_mut().find(|total| {
+            total.category_id == record.category_id 
+            && total.ft_contract_id == record.ft_contract_id
+          }) {
+            Some(total) => {
+              total.amount = U128(total.amount.0 + record.amount);
+              total.donations += 1;
+            },
+            None => totals.push(StatementTotal {
+              category_id: record.category_id,
+              beneficiary_id: self.beneficiary_by_cat_id.get(&record.category_id),
+              ft_contract_id: record.ft_contract_id.clone(),
+              amount: U128(record.amount),
+              donations: 1,
+            }),
+          }
+
+          hashed.push((
+            index,
+            record.token_id.clone(),
+            record.category_id,
+            record.amount,
+            record.ft_contract_id.clone(),
+            record.timestamp,
+          ));
+          lines.push(self.internal_ledger_json(index));
+        }
+      }
+
+      totals.sort_by(|a, b| {
+        (a.category_id, &a.ft_contract_id).cmp(&(b.category_id, &b.ft_contract_id))
+      });
+
+      let statement_hash = to_hex(&env::sha256(
+        &(&account_id, from_timestamp, to_timestamp, &hashed).try_to_vec().unwrap()
+      ));
+
+      JsonDonationStatement {
+        account_id,
+        from_timestamp,
+        to_timestamp,
+        totals,
+        lines,
+        statement_hash,
+      }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::*;
+use near_sdk::require;
+
+
+impl Contract {
+    /// Pay for `storage_used` bytes out of the storage balance of 
+    /// `account_id`; panic if it's too little. For donations that come 
+    /// without NEAR attached, e.g. through `ft_on_transfer`. 
+    pub(crate) fn internal_charge_storage(&mut self, account_id: &AccountId, storage_used: u64) {
+      if storage_used == 0 {
+        return;
+      }
+
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(storage_used);
+      let balance = self.storage_balances.get(account_id).unwrap_or(0);
+
+      require!(
+        balance >= required_cost_to_store_info,
+        format!(
+          "Storage balance too low: {} yoctoNEAR more needed. Please call deposit_storage first.",
+          required_cost_to_store_info - balance
+        )
+      );
+
+      self.storage_balances.insert(account_id, &(balance - required_cost_to_store_info));
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Deposit NEAR to pay for the storage of tokens minted for 
+    /// `account_id` (default: you) by fungible token donations. 
+    /// Returns the new storage balance. 
+    #[payable]
+    pub fn deposit_storage(&mut self, account_id: Option<AccountId>) -> U128 {
+      let initial_storage_usage = env::storage_usage();
+      let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+      let balance = self.storage_balances.get(&account_id).unwrap_or(0);
+
+      self.storage_balances.insert(&account_id, &balance);
+
+      // The balance entry itself is paid out of the deposit. 
+      let required_cost_to_store_info = env::storage_byte_cost() 
+          * Balance::from(env::storage_usage() - initial_storage_usage);
+
+      require!(
+        env::attached_deposit() > required_cost_to_store_info,
+        format!(
+          "Must attach more than {} yoctoNEAR to cover storage",
+          required_cost_to_store_info
+        )
+      );
+
+      let balance = balance + env::attached_deposit() - required_cost_to_store_info;
+      self.storage_balances.insert(&account_id, &balance);
+
+      U128(balance)
+    }
+
+
+    /// Withdraw `amount` (default: all) of your storage balance. 
+    #[payable]
+    pub fn withdraw_storage(&mut self, amount: Option<U128>) -> U128 {
+      assert_one_yocto();
+
+      let account_id = env::predecessor_account_id();
+      let balance = self.storage_balances.get(&account_id).unwrap_or(0);
+      let amount = amount.map_or(balance, |amount| amount.0);
+
+      require!(amount <= balance, "Cannot withdraw more than your storage balance.");
+
+      if amount == balance {
+        self.storage_balances.remove(&account_id);
+      } else {
+        self.storage_balances.insert(&account_id, &(balance - amount));
+      }
+
+      if amount > 0 {
+        Promise::new(account_id).transfer(amount);
+      }
+
+      U128(amount)
+    }
+
+
+    /// NEAR left to pay for storage of this account's fungible token donations. 
+    pub fn get_storage_balance(&self, account_id: AccountId) -> U128 {
+      U128(self.storage_balances.get(&account_id).unwrap_or(0))
+    }
+}
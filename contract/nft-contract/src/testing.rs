@@ -0,0 +1,151 @@
+//! Shared setup of the unit tests. 
+use crate::*;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+pub(crate) const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+
+pub(crate) fn contract_id() -> AccountId {
+  "ea_nft.near".parse().unwrap()
+}
+
+pub(crate) fn owner_id() -> AccountId {
+  accounts(0)
+}
+
+pub(crate) fn donor_id() -> AccountId {
+  accounts(1)
+}
+
+pub(crate) fn friend_id() -> AccountId {
+  accounts(2)
+}
+
+pub(crate) fn beneficiary_id() -> AccountId {
+  accounts(5)
+}
+
+
+fn context(signer_id: &AccountId, predecessor_id: &AccountId, deposit: Balance) -> VMContextBuilder {
+  let mut builder = VMContextBuilder::new();
+
+  builder
+      .current_account_id(contract_id())
+      .signer_account_id(signer_id.clone())
+      .predecessor_account_id(predecessor_id.clone())
+      .attached_deposit(deposit)
+      .account_balance(1_000 * ONE_NEAR);
+
+  builder
+}
+
+/// Next call is signed by `account_id`, attaching `deposit`. 
+pub(crate) fn set_caller(account_id: &AccountId, deposit: Balance) {
+  testing_env!(context(account_id, account_id, deposit).build());
+}
+
+/// Next call is a callback of a transaction signed by `signer_id`, 
+/// attaching `deposit`, whose promises resolved to `results`. 
+pub(crate) fn set_callback(signer_id: &AccountId, deposit: Balance, results: Vec<PromiseResult>) {
+  testing_env!(
+    context(signer_id, &contract_id(), deposit).build(),
+    VMConfig::test(),
+    RuntimeFeesConfig::test(),
+    Default::default(),
+    results,
+  );
+}
+
+
+pub(crate) fn sample_metadata(title: &str) -> TokenMetadata {
+  TokenMetadata {
+    title: Some(title.to_string()),
+    description: None,
+    media: None,
+    media_hash: None,
+    copies: None,
+    issued_at: None,
+    expires_at: None,
+    starts_at: None,
+    updated_at: None,
+    extra: None,
+    reference: None,
+    reference_hash: None,
+  }
+}
+
+/// Contract owned by `owner_id()`, with category "animal" (id 0) 
+/// donating to `beneficiary_id()`. 
+pub(crate) fn setup_contract() -> Contract {
+  set_caller(&owner_id(), 0);
+  let mut contract = Contract::new_default_meta(owner_id());
+
+  set_caller(&owner_id(), ONE_NEAR);
+  contract.generate_template("animal".to_string(), sample_metadata("Animal Welfare"), beneficiary_id());
+
+  contract
+}
+
+/// Mint `token_id` of category 0 to `owner_id`, who donated `donate_amount`, 
+/// as `nft_mint` does once the donation went through. 
+pub(crate) fn mint_token(
+  contract: &mut Contract,
+  token_id: &str,
+  owner_id: &AccountId,
+  donate_amount: Balance,
+) -> bool {
+  set_callback(owner_id, STORAGE_PER_MINT, vec![PromiseResult::Successful(vec![])]);
+  contract.nft_mint(
+    token_id.to_string(),
+    0,
+    U128(donate_amount),
+    sample_metadata(token_id),
+    owner_id.clone(),
+    None,
+    None,
+    None,
+  )
+}
+
+/// Contract of `setup_contract` with `policy`, where `donor_id()` donated 
+/// 1N for "animal-donor" and gave it to `friend_id()`. 
+pub(crate) fn setup_transferred(policy: TransferCreditPolicy) -> Contract {
+  let mut contract = setup_contract();
+
+  set_caller(&owner_id(), 0);
+  contract.set_transfer_credit_policy(policy);
+
+  mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR);
+
+  set_caller(&donor_id(), 1);
+  contract.nft_transfer(friend_id(), "animal-donor".to_string(), 0, None);
+
+  contract
+}
+
+/// Token `account_id` donates to "animal" with. 
+pub(crate) fn ordered_token(contract: &Contract, account_id: &AccountId) -> Option<TokenId> {
+  contract.tokens_per_owner_ordered
+      .get(account_id)
+      .and_then(|token_id_list| token_id_list.get(&0).cloned())
+}
+
+/// Number of donations credited to `account_id` in the ledger. 
+pub(crate) fn credited_donations(contract: &Contract, account_id: &AccountId) -> usize {
+  contract.get_donations_by_account(account_id.clone(), None, Some(100)).len()
+}
+
+/// `account_id` donates 1N to "animal" through `minting_interface`, 
+/// attaching `deposit`. 
+pub(crate) fn donate_through_interface(contract: &mut Contract, account_id: &AccountId, deposit: Balance) {
+  set_caller(account_id, deposit);
+  contract.minting_interface(
+    "-donor".to_string(),
+    vec![(CategoryKey::Id(0), U128(ONE_NEAR))].into_iter().collect(),
+    None,
+    None,
+    None,
+    None,
+  );
+}
@@ -0,0 +1,193 @@
+use crate::*;
+use near_sdk::require;
+
+
+/// A level of a category's tokens, reached once a token holds at least 
+/// `threshold` yoctoNEAR. Replaces the media and extends the title. 
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MetadataTier {
+    pub threshold: Balance,
+    pub media: String,  // URL to associated media. 
+    pub media_hash: Option<Base64VecU8>,
+    pub title_suffix: String,
+}
+
+/// The Json tier is what curators pass in and view calls return. 
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonMetadataTier {
+    pub threshold: U128,
+    pub media: String,
+    pub media_hash: Option<Base64VecU8>,
+    pub title_suffix: String,
+}
+
+
+impl Contract {
+    /// Tier of a category for this amount: 0 below the first threshold, 
+    /// else how many thresholds it reached. 
+    pub(crate) fn internal_tier_for(&self, category_id: CategoryId, donate_amount: Balance) -> u32 {
+      self.tiers_by_cat_id
+          .get(&category_id)
+          .unwrap_or_default()
+          .iter()
+          .take_while(|tier| donate_amount >= tier.threshold)
+          .count() as u32
+    }
+
+    /// Token metadata as it looks at the token's current tier. 
+    pub(crate) fn internal_tiered_metadata(
+      &self,
+      token_id: &TokenId,
+      donate_amount: Balance,
+      mut metadata: TokenMetadata,
+    ) -> TokenMetadata {
+      let category_id = match self.category_by_token_id.get(token_id) {
+        Some(category_id) => category_id,
+        None => return metadata,
+      };
+
+      let tier = self.internal_tier_for(category_id, donate_amount);
+
+      if tier == 0 {
+        return metadata;
+      }
+
+      let tiers = self.tiers_by_cat_id.get(&category_id).unwrap();
+      let tier = &tiers[tier as usize - 1];
+
+      metadata.media = Some(tier.media.clone());
+      metadata.media_hash = tier.media_hash.clone();
+      metadata.title = Some(match metadata.title {
+        Some(title) => format!("{} {}", title, tier.title_suffix),
+        None => tier.title_suffix.clone(),
+      });
+
+      metadata
+    }
+
+    /// Store `token` with `donate_amount` on it, logging it if that takes 
+    /// the token into another tier. Every change of a minted token's 
+    /// donations goes through here. 
+    pub(crate) fn internal_set_donate_amount(
+      &mut self,
+      token_id: &TokenId,
+      mut token: Token,
+      donate_amount: Balance,
+    ) {
+      let old_donate_amount = token.donate_amount;
+      token.donate_amount = donate_amount;
+      self.tokens_by_id.insert(token_id, &token);
+
+      if let Some(category_id) = self.category_by_token_id.get(token_id) {
+        self.internal_log_tier_change(token_id, category_id, old_donate_amount, donate_amount);
+      }
+    }
+
+    /// Log it if a donation took the token into another tier. 
+    pub(crate) fn internal_log_tier_change(
+      &self,
+      token_id: &TokenId,
+      category_id: CategoryId,
+      old_donate_amount: Balance,
+      donate_amount: Balance,
+    ) {
+      let old_tier = self.internal_tier_for(category_id, old_donate_amount);
+      let new_tier = self.internal_tier_for(category_id, donate_amount);
+
+      if old_tier == new_tier {
+        return;
+      }
+
+      let tier_log: EventLog = EventLog {
+        standard: DONATION_STANDARD_NAME.to_string(),
+        version : DONATION_EVENT_VERSION.to_string(),
+        event   : EventLogVariant::TokenTierChange(vec![TierLog {
+          token_id     : token_id.to_string(),
+          category_id  : category_id.to_string(),
+          old_tier     : old_tier.to_string(),
+          new_tier     : new_tier.to_string(),
+          donate_amount: donate_amount.to_string(),
+        }]),
+      };
+
+      env::log_str(&tier_log.to_string());
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Replace the tiers of a category. Thresholds must go up strictly, 
+    /// starting above 0; pass none to drop tiers. Curators only. 
+    /// 
+    /// Attach enough for storage if the tiers grow; extra is refunded. 
+    #[payable]
+    pub fn set_category_tiers(&mut self, category_id: CategoryKey, tiers: Vec<JsonMetadataTier>) {
+      let initial_storage_usage = env::storage_usage();
+
+      self.internal_assert_role(Role::Curator);
+      let category_id = self.internal_category_id(&category_id);
+
+      let mut last_threshold: Balance = 0;
+
+      for tier in tiers.iter() {
+        require!(
+          tier.threshold.0 > last_threshold,
+          "Tier thresholds must be above 0 and strictly increasing."
+        );
+        last_threshold = tier.threshold.0;
+      }
+
+      if tiers.is_empty() {
+        self.tiers_by_cat_id.remove(&category_id);
+      } else {
+        let tiers: Vec<MetadataTier> = tiers
+            .into_iter()
+            .map(|tier| MetadataTier {
+              threshold: tier.threshold.0,
+              media: tier.media,
+              media_hash: tier.media_hash,
+              title_suffix: tier.title_suffix,
+            })
+            .collect();
+
+        self.tiers_by_cat_id.insert(&category_id, &tiers);
+      }
+
+      self.internal_log_category_event(category_id, EventLogVariant::CategoryUpdate);
+
+      let required_storage = env::storage_usage().saturating_sub(initial_storage_usage);
+      refund_deposit(required_storage, env::predecessor_account_id());
+    }
+
+
+    pub fn get_category_tiers(&self, category_id: CategoryKey) -> Vec<JsonMetadataTier> {
+      let category_id = self.internal_category_id(&category_id);
+
+      self.tiers_by_cat_id
+          .get(&category_id)
+          .unwrap_or_default()
+          .into_iter()
+          .map(|tier| JsonMetadataTier {
+            threshold: U128(tier.threshold),
+            media: tier.media,
+            media_hash: tier.media_hash,
+            title_suffix: tier.title_suffix,
+          })
+          .collect()
+    }
+
+
+    /// Tier a token is at: 0 for none reached yet. 
+    pub fn get_token_tier(&self, token_id: TokenId) -> u32 {
+      let token = expect_lightweight(
+        self.tokens_by_id.get(&token_id),
+        "This token_id cannot be found."
+      );
+
+      self.category_by_token_id
+          .get(&token_id)
+          .map_or(0, |category_id| self.internal_tier_for(category_id, token.donate_amount))
+    }
+}
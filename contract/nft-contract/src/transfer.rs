@@ -0,0 +1,201 @@
+use crate::*;
+use std::collections::HashSet;
+
+
+/// Who keeps the ledger credit of a token's donations once it's transferred. 
+/// Either way the token keeps its `donate_amount` and the ledger its records. 
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TransferCreditPolicy {
+    /// The donations stay under the donor's account in the ledger. 
+    StaysWithDonor,
+    /// The donations move to the new owner's account in the ledger. 
+    TravelsWithToken,
+}
+
+
+impl Contract {
+    /// Bookkeeping after `token_id` moved from `old_owner_id` to `new_owner_id`: 
+    /// the ordered category maps of both, and the ledger credit per policy. 
+    pub(crate) fn internal_after_transfer(
+      &mut self,
+      token_id: &TokenId,
+      old_owner_id: &AccountId,
+      new_owner_id: &AccountId,
+    ) {
+      if let Some(category_id) = self.category_by_token_id.get(token_id) {
+        let mut token_id_list = self.internal_ordered_tokens(old_owner_id, old_owner_id);
+        if token_id_list.get(&category_id) == Some(token_id) {
+          token_id_list.remove(&category_id);
+          self.internal_set_ordered_tokens(old_owner_id, old_owner_id, &token_id_list);
+        }
+
+        // Keep a token the new owner already has for this category. 
+        let mut token_id_list = self.internal_ordered_tokens(new_owner_id, new_owner_id);
+        if !token_id_list.contains_key(&category_id) {
+          token_id_list.insert(category_id, token_id.clone());
+          self.internal_set_ordered_tokens(new_owner_id, new_owner_id, &token_id_list);
+        }
+      }
+
+      if self.transfer_credit_policy == TransferCreditPolicy::TravelsWithToken {
+        self.internal_move_ledger_credit(token_id, old_owner_id, new_owner_id);
+      }
+    }
+
+    /// Move the ledger entries of `token_id` credited to `from` over to `to`. 
+    fn internal_move_ledger_credit(&mut self, token_id: &TokenId, from: &AccountId, to: &AccountId) {
+      let token_indexes: HashSet<DonationIndex> = match self.ledger_by_token_id.get(token_id) {
+        Some(indexes) => indexes.iter().collect(),
+        None => return,
+      };
+
+      let mut from_indexes = match self.ledger_by_account.get(from) {
+        Some(indexes) => indexes,
+        None => return,
+      };
+
+      let (moved, kept): (Vec<DonationIndex>, Vec<DonationIndex>) = from_indexes
+          .iter()
+          .partition(|index| token_indexes.contains(index));
+
+      if moved.is_empty() {
+        return;
+      }
+
+      from_indexes.clear();
+      from_indexes.extend(kept);
+      self.ledger_by_account.insert(from, &from_indexes);
+
+      for index in moved {
+        self.internal_index_ledger_by_account(to, index);
+      }
+    }
+
+    /// `token_id` if it's free, else the first free `token_id-2`, `token_id-3`... 
+    /// so someone who gave their token away can still mint a new one. 
+    /// Burned IDs are never free again. 
+    pub(crate) fn internal_unique_token_id(&self, token_id: TokenId) -> TokenId {
+      let is_free = |token_id: &TokenId| {
+        self.tokens_by_id.get(token_id).is_none() && !self.burned_token_ids.contains(token_id)
+      };
+
+      if is_free(&token_id) {
+        return token_id;
+      }
+
+      (2..)
+          .map(|n| format!("{}-{}", token_id, n))
+          .find(|candidate| is_free(candidate))
+          .unwrap()
+    }
+}
+
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only. 
+    pub fn set_transfer_credit_policy(&mut self, policy: TransferCreditPolicy) {
+      self.internal_assert_role(Role::Owner);
+      self.transfer_credit_policy = policy;
+    }
+
+
+    pub fn get_transfer_credit_policy(&self) -> TransferCreditPolicy {
+      self.transfer_credit_policy
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    /// The sender gave "animal-donor" away, so `minting_interface` mints 
+    /// them a new token; `nft_mint` picks a free id for it. 
+    fn sender_donates_again(policy: TransferCreditPolicy) -> Contract {
+      let mut contract = setup_transferred(policy);
+
+      donate_through_interface(&mut contract, &donor_id(), ONE_NEAR + STORAGE_PER_MINT);
+      assert_eq!(ordered_token(&contract, &donor_id()), Some("animal-donor-2".to_string()));
+
+      assert!(mint_token(&mut contract, "animal-donor", &donor_id(), ONE_NEAR));
+      assert_eq!(contract.nft_token("animal-donor-2".to_string()).unwrap().owner_id, donor_id());
+      assert_eq!(contract.nft_token("animal-donor".to_string()).unwrap().owner_id, friend_id());
+      assert_eq!(ordered_token(&contract, &friend_id()), Some("animal-donor".to_string()));
+
+      contract
+    }
+
+    /// The receiver's `minting_interface` donates on top of the token 
+    /// they received, through `donate_and_update`. 
+    fn receiver_donates(policy: TransferCreditPolicy) -> Contract {
+      let mut contract = setup_transferred(policy);
+
+      donate_through_interface(&mut contract, &friend_id(), ONE_NEAR);
+      assert_eq!(ordered_token(&contract, &friend_id()), Some("animal-donor".to_string()));
+
+      set_callback(&friend_id(), ONE_NEAR, vec![]);
+      contract.donate_and_update("animal-donor".to_string(), U128(ONE_NEAR), None);
+
+      set_callback(&friend_id(), 0, vec![PromiseResult::Successful(vec![])]);
+      assert!(contract.on_donate_update(
+        "animal-donor".to_string(),
+        U128(ONE_NEAR),
+        false,
+        None,
+      ));
+
+      assert_eq!(contract.tokens_by_id.get(&"animal-donor".to_string()).unwrap().donate_amount, 2 * ONE_NEAR);
+      assert_eq!(ordered_token(&contract, &donor_id()), None);
+
+      contract
+    }
+
+
+    #[test]
+    fn transfer_moves_ordered_token() {
+      let contract = setup_transferred(TransferCreditPolicy::StaysWithDonor);
+
+      assert_eq!(ordered_token(&contract, &donor_id()), None);
+      assert_eq!(ordered_token(&contract, &friend_id()), Some("animal-donor".to_string()));
+    }
+
+
+    #[test]
+    fn sender_donates_again_credit_stays_with_donor() {
+      let contract = sender_donates_again(TransferCreditPolicy::StaysWithDonor);
+
+      assert_eq!(credited_donations(&contract, &donor_id()), 2);
+      assert_eq!(credited_donations(&contract, &friend_id()), 0);
+    }
+
+
+    #[test]
+    fn sender_donates_again_credit_travels_with_token() {
+      let contract = sender_donates_again(TransferCreditPolicy::TravelsWithToken);
+
+      assert_eq!(credited_donations(&contract, &donor_id()), 1);
+      assert_eq!(credited_donations(&contract, &friend_id()), 1);
+    }
+
+
+    #[test]
+    fn receiver_donates_credit_stays_with_donor() {
+      let contract = receiver_donates(TransferCreditPolicy::StaysWithDonor);
+
+      assert_eq!(credited_donations(&contract, &donor_id()), 1);
+      assert_eq!(credited_donations(&contract, &friend_id()), 1);
+    }
+
+
+    #[test]
+    fn receiver_donates_credit_travels_with_token() {
+      let contract = receiver_donates(TransferCreditPolicy::TravelsWithToken);
+
+      assert_eq!(credited_donations(&contract, &donor_id()), 0);
+      assert_eq!(credited_donations(&contract, &friend_id()), 2);
+    }
+}
@@ -0,0 +1,9 @@
+#!/bin/bash
+# Deploy new code over the running contract and migrate its state, 
+# keeping every token. Unlike redeploy.sh, nothing is deleted. 
+
+bash build.sh
+export CONTRACT=ea_nft.wabinab.testnet
+export OWNER=${OWNER:-$CONTRACT}
+
+near call $CONTRACT upgrade --base64 "$(base64 -w0 res/output_s.wasm)" --accountId $OWNER --gas 300000000000000
@@ -0,0 +1,203 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use serde::Deserialize;
+
+const YOCTO_DECIMALS: usize = 24;
+
+
+#[derive(Deserialize)]
+struct Total {
+    category_id: u16,
+    beneficiary_id: Option<String>,
+    ft_contract_id: Option<String>,
+    amount: String,
+    donations: u64,
+}
+
+#[derive(Deserialize)]
+struct Line {
+    index: u64,
+    token_id: String,
+    category_id: u16,
+    amount: String,
+    ft_contract_id: Option<String>,
+    timestamp: u64,
+    block_height: u64,
+}
+
+#[derive(Deserialize)]
+struct Statement {
+    account_id: String,
+    from_timestamp: u64,
+    to_timestamp: u64,
+    totals: Vec<Total>,
+    lines: Vec<Line>,
+    statement_hash: String,
+}
+
+
+/// Yocto string to a NEAR decimal string, without trailing zeros. 
+fn yocto_to_near(amount: &str) -> String {
+  let padded = format!("{:0>width$}", amount, width = YOCTO_DECIMALS + 1);
+  let (whole, fraction) = padded.split_at(padded.len() - YOCTO_DECIMALS);
+  let fraction = fraction.trim_end_matches('0');
+
+  if fraction.is_empty() {
+    whole.to_string()
+  } else {
+    format!("{}.{}", whole, fraction)
+  }
+}
+
+fn asset(ft_contract_id: &Option<String>) -> &str {
+  ft_contract_id.as_deref().unwrap_or("NEAR")
+}
+
+fn display_amount(amount: &str, ft_contract_id: &Option<String>) -> String {
+  match ft_contract_id {
+    None => yocto_to_near(amount),
+    Some(_) => amount.to_string(),
+  }
+}
+
+/// Nanosecond timestamp to `YYYY-MM-DD HH:MM:SS` UTC. 
+fn format_timestamp(nanos: u64) -> String {
+  let secs = nanos / 1_000_000_000;
+  let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+
+  // civil from days, Howard Hinnant's algorithm. 
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z - era * 146_097;
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  format!(
+    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+    year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60
+  )
+}
+
+fn csv_field(value: &str) -> String {
+  if value.contains([',', '"', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+
+fn render_text(statement: &Statement) -> String {
+  let mut out = String::new();
+
+  out.push_str("STATEMENT OF DONATIONS\n\n");
+  out.push_str(&format!("Donor:   {}\n", statement.account_id));
+  out.push_str(&format!("From:    {} UTC\n", format_timestamp(statement.from_timestamp)));
+  out.push_str(&format!("To:      {} UTC\n", format_timestamp(statement.to_timestamp)));
+  out.push_str(&format!("Hash:    {}\n\n", statement.statement_hash));
+
+  out.push_str("TOTALS\n");
+  if statement.totals.is_empty() {
+    out.push_str("  No donations in this period.\n");
+  }
+  for total in &statement.totals {
+    out.push_str(&format!(
+      "  Category {:<5} {:>28} {:<20} {:>4} donation(s)  beneficiary: {}\n",
+      total.category_id,
+      display_amount(&total.amount, &total.ft_contract_id),
+      asset(&total.ft_contract_id),
+      total.donations,
+      total.beneficiary_id.as_deref().unwrap_or("-"),
+    ));
+  }
+
+  out.push_str("\nDONATIONS\n");
+  for line in &statement.lines {
+    out.push_str(&format!(
+      "  #{:<8} {} UTC  category {:<5} {:>28} {:<20} token {}  block {}\n",
+      line.index,
+      format_timestamp(line.timestamp),
+      line.category_id,
+      display_amount(&line.amount, &line.ft_contract_id),
+      asset(&line.ft_contract_id),
+      line.token_id,
+      line.block_height,
+    ));
+  }
+
+  out
+}
+
+fn render_csv(statement: &Statement) -> String {
+  let mut out = String::from(
+    "index,date_utc,category_id,beneficiary_id,token_id,asset,amount,block_height\n"
+  );
+
+  for line in &statement.lines {
+    let beneficiary_id = statement.totals.iter()
+        .find(|total| total.category_id == line.category_id)
+        .and_then(|total| total.beneficiary_id.clone())
+        .unwrap_or_default();
+
+    out.push_str(&format!(
+      "{},{},{},{},{},{},{},{}\n",
+      line.index,
+      format_timestamp(line.timestamp),
+      line.category_id,
+      csv_field(&beneficiary_id),
+      csv_field(&line.token_id),
+      csv_field(asset(&line.ft_contract_id)),
+      display_amount(&line.amount, &line.ft_contract_id),
+      line.block_height,
+    ));
+  }
+
+  out.push_str(&format!("# donor,{}\n", csv_field(&statement.account_id)));
+  out.push_str(&format!("# statement_hash,{}\n", statement.statement_hash));
+  out
+}
+
+
+fn main() {
+  let mut csv = false;
+  let mut path: Option<String> = None;
+
+  for arg in env::args().skip(1) {
+    match arg.as_str() {
+      "--csv" => csv = true,
+      "--text" => csv = false,
+      _ => path = Some(arg),
+    }
+  }
+
+  let input = match &path {
+    Some(path) => fs::read_to_string(path),
+    None => {
+      let mut buffer = String::new();
+      io::stdin().read_to_string(&mut buffer).map(|_| buffer)
+    },
+  };
+
+  let input = input.unwrap_or_else(|err| {
+    eprintln!("Cannot read statement: {}", err);
+    process::exit(1);
+  });
+
+  let statement: Statement = serde_json::from_str(&input).unwrap_or_else(|err| {
+    eprintln!("Invalid statement JSON: {}", err);
+    process::exit(1);
+  });
+
+  if csv {
+    print!("{}", render_csv(&statement));
+  } else {
+    print!("{}", render_text(&statement));
+  }
+}